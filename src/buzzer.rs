@@ -0,0 +1,44 @@
+use std::{sync::Arc, time::Duration};
+
+use tokio::task::JoinHandle;
+
+use crate::gpio_chip::{Chip, Output};
+
+/// A GPIO buzzer or horn sounded in on/off patterns, like range whistle signals
+pub struct Buzzer {
+    line: Arc<Output>,
+    playing: Option<JoinHandle<()>>,
+}
+impl Buzzer {
+    pub fn new(chip: &mut Chip, line: u32) -> Result<Self, String> {
+        let line = chip.request_output(line, "timer-buzzer")?;
+        return Ok(Self {
+            line: Arc::new(line),
+            playing: None,
+        });
+    }
+    /// Sounds a pattern of alternating on and off times in milliseconds, cutting short any
+    /// pattern still playing
+    pub fn play(&mut self, pattern: &[u64]) {
+        if let Some(playing) = self.playing.take() {
+            playing.abort();
+            // It may have been cut off with the buzzer on
+            set(&self.line, false);
+        }
+        let line = Arc::clone(&self.line);
+        let pattern = pattern.to_vec();
+        self.playing = Some(tokio::spawn(async move {
+            for (i, ms) in pattern.into_iter().enumerate() {
+                set(&line, i % 2 == 0);
+                tokio::time::sleep(Duration::from_millis(ms)).await;
+            }
+            set(&line, false);
+        }));
+    }
+}
+
+fn set(line: &Output, on: bool) {
+    if let Err(err) = line.set(on) {
+        log::warn!("Failed to set buzzer line: {err}");
+    }
+}
@@ -0,0 +1,121 @@
+use std::{collections::HashMap, path::Path};
+
+use futures::StreamExt;
+use gpio_cdev::{EventRequestFlags, EventType, LineHandle, LineRequestFlags};
+
+use crate::{Bias, ButtonEvent, ButtonSide, ButtonStream, Edges, InputLine};
+
+/// gpio-cdev can't ask the kernel to debounce, so it's done as the events come in
+pub const DEBOUNCES_IN_KERNEL: bool = false;
+
+// Bias request flags from linux/gpio.h, which gpio-cdev doesn't name
+const GPIOHANDLE_REQUEST_BIAS_PULL_UP: u32 = 1 << 5;
+const GPIOHANDLE_REQUEST_BIAS_PULL_DOWN: u32 = 1 << 6;
+const GPIOHANDLE_REQUEST_BIAS_DISABLE: u32 = 1 << 7;
+
+/// A GPIO chip, reached through gpio-cdev
+pub struct Chip {
+    chip: gpio_cdev::Chip,
+}
+impl Chip {
+    /// Opens a GPIO chip by path, or by its label or name
+    pub fn open(chip: &Path) -> Result<Self, String> {
+        if chip.is_absolute() {
+            return gpio_cdev::Chip::new(chip)
+                .map(|chip| Self { chip })
+                .map_err(|err| format!("failed to open {chip:?}: {err}"));
+        }
+        let chips =
+            gpio_cdev::chips().map_err(|err| format!("failed to list GPIO chips: {err}"))?;
+        for found in chips.flatten() {
+            if chip == Path::new(found.label()) || chip == Path::new(found.name()) {
+                return Ok(Self { chip: found });
+            }
+        }
+        return Err(format!("there is no GPIO chip labelled {chip:?}"));
+    }
+    /// Offsets of the lines the kernel has named
+    pub fn line_names(&self) -> Result<HashMap<String, u32>, String> {
+        let mut names = HashMap::new();
+        for line in self.chip.lines() {
+            let info = line
+                .info()
+                .map_err(|err| format!("failed to read line {}: {err}", line.offset()))?;
+            if let Some(name) = info.name() {
+                names.insert(name.to_owned(), line.offset());
+            }
+        }
+        return Ok(names);
+    }
+    /// Requests a line as an output, starting off
+    pub fn request_output(&mut self, offset: u32, consumer: &str) -> Result<Output, String> {
+        return self
+            .chip
+            .get_line(offset)
+            .and_then(|line| line.request(LineRequestFlags::OUTPUT, 0, consumer))
+            .map(|handle| Output { handle })
+            .map_err(|err| err.to_string());
+    }
+    /// Watches the buttons, passing on each button and whether it's now pressed
+    pub fn watch_buttons(
+        &mut self,
+        inputs: Vec<(InputLine, ButtonSide, Edges)>,
+    ) -> Result<ButtonStream, String> {
+        let buttons = inputs
+            .into_iter()
+            .map(|(line, side, edges)| self.watch_button(line, side, edges))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|err| format!("failed to request the button lines: {err}"))?;
+        return Ok(futures::stream::select_all(buttons).boxed_local());
+    }
+    /// Requests events for one input line
+    fn watch_button(
+        &mut self,
+        line: InputLine,
+        side: ButtonSide,
+        edges: Edges,
+    ) -> Result<impl futures::Stream<Item = ButtonEvent>, gpio_cdev::Error> {
+        let edges = match edges {
+            Edges::Both => EventRequestFlags::BOTH_EDGES,
+            Edges::Rising => EventRequestFlags::RISING_EDGE,
+        };
+        let handle = gpio_cdev::AsyncLineEventHandle::new(
+            self.chip
+                .get_line(line.offset())?
+                .events(request_flags(&line), edges, "read-input")?,
+        )?;
+        return Ok(handle.map(move |event| {
+            return event
+                .map(|event| (side, event.event_type() == EventType::RisingEdge))
+                .map_err(|err| format!("failed to read the {side:?} button: {err}"));
+        }));
+    }
+}
+
+/// Flags for requesting an input line
+fn request_flags(line: &InputLine) -> LineRequestFlags {
+    let mut flags = LineRequestFlags::INPUT;
+    if line.active_low() {
+        flags |= LineRequestFlags::ACTIVE_LOW;
+    }
+    let bias = match line.bias() {
+        Bias::AsIs => 0,
+        Bias::PullUp => GPIOHANDLE_REQUEST_BIAS_PULL_UP,
+        Bias::PullDown => GPIOHANDLE_REQUEST_BIAS_PULL_DOWN,
+        Bias::Disabled => GPIOHANDLE_REQUEST_BIAS_DISABLE,
+    };
+    return flags | LineRequestFlags::from_bits_retain(bias);
+}
+
+/// A line requested as an output
+pub struct Output {
+    handle: LineHandle,
+}
+impl Output {
+    pub fn set(&self, on: bool) -> Result<(), String> {
+        return self
+            .handle
+            .set_value(u8::from(on))
+            .map_err(|err| err.to_string());
+    }
+}
@@ -0,0 +1,67 @@
+use std::{path::Path, time::Duration};
+
+use serde::{Deserialize, Serialize};
+
+use crate::SequencePhase;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+pub struct TimerCheckpoint {
+    pub running: bool,
+    pub elapsed: Duration,
+    /// Length of a countdown, which may have been switched by a preset
+    pub duration: Option<Duration>,
+    /// Time granted on top of the countdown
+    #[serde(default)]
+    pub extra: Duration,
+}
+
+/// Snapshot of where the timers were, restored on startup after a crash or power loss
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Checkpoint {
+    /// Unix time in milliseconds when the checkpoint was written
+    pub saved_at_ms: i64,
+    pub round: Option<usize>,
+    pub phase: SequencePhase,
+    pub end_number: usize,
+    pub end_started: bool,
+    pub line_index: usize,
+    pub game: TimerCheckpoint,
+    pub left: TimerCheckpoint,
+    pub right: TimerCheckpoint,
+}
+impl Checkpoint {
+    pub fn load(path: &Path) -> Option<Self> {
+        let file = match std::fs::File::open(path) {
+            Ok(file) => file,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return None,
+            Err(err) => {
+                log::error!("Failed to open checkpoint {path:?}: {err}");
+                return None;
+            }
+        };
+        return match serde_yaml::from_reader(file) {
+            Ok(checkpoint) => Some(checkpoint),
+            Err(err) => {
+                log::error!("Ignoring unreadable checkpoint {path:?}: {err}");
+                None
+            }
+        };
+    }
+    /// Writes the checkpoint alongside `path` and moves it into place, so a power loss
+    /// mid-write can't leave a truncated file behind
+    pub fn save(&self, path: &Path) {
+        let temp_path = path.with_extension("tmp");
+        let result = std::fs::File::create(&temp_path)
+            .map_err(|err| err.to_string())
+            .and_then(|file| serde_yaml::to_writer(file, self).map_err(|err| err.to_string()))
+            .and_then(|_| std::fs::rename(&temp_path, path).map_err(|err| err.to_string()));
+        if let Err(err) = result {
+            log::error!("Failed to save checkpoint {path:?}: {err}");
+        }
+    }
+    /// How long the application was down since the checkpoint was written
+    pub fn downtime(&self) -> Duration {
+        let downtime_ms = chrono::Local::now().timestamp_millis() - self.saved_at_ms;
+        return Duration::from_millis(downtime_ms.max(0) as u64);
+    }
+}
@@ -0,0 +1,216 @@
+use std::path::{Path, PathBuf};
+
+use serde_yaml::{Mapping, Value};
+
+use crate::Config;
+
+/// Parses a YAML, TOML or JSON file into a generic value, going by the file extension
+pub fn read_value(path: &Path) -> Result<Value, String> {
+    let contents = std::fs::read_to_string(path).map_err(|err| err.to_string())?;
+    return match path.extension().and_then(|extension| extension.to_str()) {
+        Some("toml") => toml::from_str(&contents).map_err(|err| err.to_string()),
+        Some("json") => serde_json::from_str(&contents).map_err(|err| err.to_string()),
+        _ => serde_yaml::from_str(&contents).map_err(|err| err.to_string()),
+    };
+}
+
+/// Overlays `overlay` onto `base`. Mappings are merged key by key; anything else in the
+/// overlay replaces what was there.
+pub fn merge(base: &mut Value, overlay: Value) {
+    match (base, overlay) {
+        (Value::Mapping(base), Value::Mapping(overlay)) => {
+            for (key, value) in overlay {
+                match base.get_mut(&key) {
+                    Some(existing) => merge(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, overlay) => *base = overlay,
+    }
+}
+
+/// Files listed under `include`, relative to the file including them
+fn include_paths(path: &Path, value: &Value) -> Result<Vec<PathBuf>, String> {
+    let names = match value.get("include") {
+        None => return Ok(Vec::new()),
+        Some(Value::String(name)) => vec![name.as_str()],
+        Some(Value::Sequence(names)) => names
+            .iter()
+            .map(|name| name.as_str().ok_or("include must only list file paths"))
+            .collect::<Result<_, _>>()?,
+        Some(_) => return Err("include must be a file path or a list of them".to_owned()),
+    };
+    let dir = path.parent().unwrap_or(Path::new("."));
+    return Ok(names.into_iter().map(|name| dir.join(name)).collect());
+}
+
+/// Reads and merges everything `value` (read from `path`) includes, noting each file read in
+/// `files`. Later includes take precedence over earlier ones, and each file over the files it
+/// includes.
+fn read_includes(
+    path: &Path,
+    value: &Value,
+    parents: &mut Vec<PathBuf>,
+    files: &mut Vec<PathBuf>,
+) -> Result<Value, String> {
+    let mut merged = Value::Mapping(Mapping::new());
+    for include in include_paths(path, value)? {
+        let canonical = include.canonicalize().unwrap_or_else(|_| include.clone());
+        if parents.contains(&canonical) {
+            return Err(format!("{include:?} includes itself"));
+        }
+        let included = read_value(&include).map_err(|err| format!("{include:?}: {err}"))?;
+        files.push(include.clone());
+        parents.push(canonical);
+        let nested = read_includes(&include, &included, parents, files)?;
+        parents.pop();
+        merge(&mut merged, nested);
+        merge(&mut merged, included);
+    }
+    return Ok(merged);
+}
+
+/// A setting to write back to the config, e.g. to keep something changed at runtime
+pub struct SettingChange {
+    pub description: String,
+    pub path: Vec<&'static str>,
+    pub value: Option<Value>,
+}
+
+/// The value as a mapping, replacing it with an empty one if it's anything else
+fn make_mapping(value: &mut Value) -> &mut Mapping {
+    if !value.is_mapping() {
+        *value = Value::Mapping(Mapping::new());
+    }
+    return value.as_mapping_mut().unwrap();
+}
+
+/// Where the config came from, kept so it can be re-resolved when the file or the active
+/// profile changes
+#[derive(Clone)]
+pub struct ConfigSource {
+    pub path: PathBuf,
+    raw: Value,
+    // Everything the file includes, merged. Kept apart so saving only writes the file itself.
+    included: Value,
+    includes: Vec<PathBuf>,
+    profile: Option<String>,
+}
+impl ConfigSource {
+    pub fn load(path: PathBuf, profile: Option<String>) -> Result<Self, String> {
+        let mut source = Self {
+            path,
+            raw: Value::Null,
+            included: Value::Null,
+            includes: Vec::new(),
+            profile,
+        };
+        source.reload()?;
+        return Ok(source);
+    }
+    /// Re-reads the file and its includes, keeping the active profile
+    pub fn reload(&mut self) -> Result<(), String> {
+        let raw = read_value(&self.path)?;
+        let canonical = self
+            .path
+            .canonicalize()
+            .unwrap_or_else(|_| self.path.clone());
+        let mut includes = Vec::new();
+        self.included = read_includes(&self.path, &raw, &mut vec![canonical], &mut includes)?;
+        self.includes = includes;
+        self.raw = raw;
+        return Ok(());
+    }
+    /// The file itself and every file it includes, directly or not
+    pub fn files(&self) -> Vec<PathBuf> {
+        return std::iter::once(self.path.clone())
+            .chain(self.includes.iter().cloned())
+            .collect();
+    }
+    /// The included files with the file itself merged over them
+    fn merged(&self) -> Value {
+        let mut merged = self.included.clone();
+        merge(&mut merged, self.raw.clone());
+        return merged;
+    }
+    pub fn profile(&self) -> Option<&str> {
+        return self.profile.as_deref();
+    }
+    pub fn set_profile(&mut self, profile: Option<String>) {
+        self.profile = profile;
+    }
+    /// Names of the profiles defined in the config, in the order they appear
+    pub fn profile_names(&self) -> Vec<String> {
+        let merged = self.merged();
+        let Some(Value::Mapping(profiles)) = merged.get("profiles") else {
+            return Vec::new();
+        };
+        return profiles
+            .keys()
+            .filter_map(|name| name.as_str().map(str::to_owned))
+            .collect();
+    }
+    /// Sets (or with `None`, removes) a setting such as `["left_timer", "color"]`, within the
+    /// active profile if there is one so the change isn't hidden by it
+    pub fn set(&mut self, path: &[&str], value: Option<Value>) {
+        let Some((last, parents)) = path.split_last() else {
+            return;
+        };
+        let mut target = &mut self.raw;
+        let profile_path = self
+            .profile
+            .iter()
+            .map(|profile| ["profiles", profile.as_str()]);
+        for key in profile_path.flatten().chain(parents.iter().copied()) {
+            target = make_mapping(target)
+                .entry(Value::from(key))
+                .or_insert(Value::Null);
+        }
+        let mapping = make_mapping(target);
+        match value {
+            Some(value) => {
+                mapping.insert(Value::from(*last), value);
+            }
+            None => {
+                mapping.remove(*last);
+            }
+        }
+    }
+    /// Writes the settings back to the file in its original format. Comments aren't kept.
+    pub fn save(&self) -> Result<(), String> {
+        let contents = match self
+            .path
+            .extension()
+            .and_then(|extension| extension.to_str())
+        {
+            Some("toml") => toml::to_string_pretty(&self.raw).map_err(|err| err.to_string())?,
+            Some("json") => {
+                serde_json::to_string_pretty(&self.raw).map_err(|err| err.to_string())?
+            }
+            _ => serde_yaml::to_string(&self.raw).map_err(|err| err.to_string())?,
+        };
+        return std::fs::write(&self.path, contents).map_err(|err| err.to_string());
+    }
+    /// The config with the active profile's settings merged over the top
+    pub fn resolve(&self) -> Result<Config, String> {
+        let mut config = self.merged();
+        let profiles = match config {
+            Value::Mapping(ref mut config) => {
+                config.remove("include");
+                config.remove("profiles")
+            }
+            _ => None,
+        };
+        if let Some(ref name) = self.profile {
+            let profile = profiles
+                .as_ref()
+                .and_then(|profiles| profiles.get(name.as_str()))
+                .ok_or_else(|| format!("there is no profile named {name:?}"))?;
+            merge(&mut config, profile.clone());
+        }
+        return serde_yaml::from_value(config).map_err(|err| err.to_string());
+    }
+}
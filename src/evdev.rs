@@ -0,0 +1,108 @@
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{self, Read},
+    mem,
+    os::fd::AsRawFd,
+    path::PathBuf,
+    time::Duration,
+};
+
+use futures::{channel::mpsc, stream::LocalBoxStream, StreamExt};
+use serde::{Deserialize, Serialize};
+
+use crate::ButtonSide;
+
+// From linux/input.h
+const EV_KEY: u16 = 0x01;
+const EVIOCGRAB: libc::c_ulong = 0x4004_4590;
+// Key event values other than press (1) and release (0)
+const KEY_REPEAT: i32 = 2;
+// How long to wait before reopening a device that failed or was unplugged
+const REOPEN_INTERVAL: Duration = Duration::from_secs(2);
+
+fn default_grab() -> bool {
+    return true;
+}
+
+/// A `/dev/input/event*` device, such as a USB button box or a keyboard, whose keys act as
+/// the timer buttons
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct InputDeviceConfig {
+    /// Path to the device. Paths under `/dev/input/by-id/` stay the same across reboots.
+    pub device: PathBuf,
+    /// Key code for each button, as shown by `evtest`
+    pub keys: HashMap<ButtonSide, u16>,
+    /// Takes the device for ourselves, so its keys don't also reach the window
+    #[serde(default = "default_grab")]
+    pub grab: bool,
+}
+
+/// Passes on key events from the device until it fails, or returns `Ok` once nothing is
+/// listening any more
+fn read_events(
+    config: &InputDeviceConfig,
+    sides: &HashMap<u16, ButtonSide>,
+    sender: &mpsc::UnboundedSender<(ButtonSide, bool)>,
+    failing: &mut bool,
+) -> io::Result<()> {
+    let mut file = File::open(&config.device)?;
+    if config.grab {
+        // SAFETY: EVIOCGRAB takes its flag by value and doesn't touch our memory
+        let result = unsafe { libc::ioctl(file.as_raw_fd(), EVIOCGRAB, 1 as libc::c_int) };
+        if result < 0 {
+            return Err(io::Error::last_os_error());
+        }
+    }
+    log::info!("Reading buttons from {:?}", config.device);
+    *failing = false;
+    let mut buffer = [0u8; mem::size_of::<libc::input_event>()];
+    loop {
+        file.read_exact(&mut buffer)?;
+        // SAFETY: input_event is plain data, and the buffer is exactly its size
+        let event: libc::input_event = unsafe { std::ptr::read_unaligned(buffer.as_ptr().cast()) };
+        if event.type_ != EV_KEY || event.value == KEY_REPEAT {
+            continue;
+        }
+        let Some(&side) = sides.get(&event.code) else {
+            continue;
+        };
+        if sender.unbounded_send((side, event.value != 0)).is_err() {
+            return Ok(());
+        }
+    }
+}
+
+/// Reads each device's keys on its own thread, passing on the button and whether it's now
+/// pressed. Devices that fail or are unplugged are reopened.
+pub fn watch_devices(devices: &[InputDeviceConfig]) -> LocalBoxStream<'static, (ButtonSide, bool)> {
+    if devices.is_empty() {
+        return futures::stream::pending().boxed_local();
+    }
+    let (sender, receiver) = mpsc::unbounded();
+    for config in devices {
+        let config = config.clone();
+        let sender = sender.clone();
+        let sides: HashMap<u16, ButtonSide> = config
+            .keys
+            .iter()
+            .map(|(&side, &code)| (code, side))
+            .collect();
+        std::thread::spawn(move || {
+            let mut failing = false;
+            loop {
+                match read_events(&config, &sides, &sender, &mut failing) {
+                    Ok(()) => return,
+                    Err(err) => {
+                        if !failing {
+                            log::warn!("Input device {:?} unavailable: {err}", config.device);
+                        }
+                        failing = true;
+                    }
+                }
+                std::thread::sleep(REOPEN_INTERVAL);
+            }
+        });
+    }
+    return receiver.boxed_local();
+}
@@ -0,0 +1,37 @@
+use std::{
+    fs::{File, OpenOptions},
+    io::Write,
+    path::Path,
+    time::Duration,
+};
+
+/// Append-only CSV record of every timer start, stop and clear
+pub struct EventLog {
+    file: File,
+}
+impl EventLog {
+    pub fn open(path: &Path) -> std::io::Result<Self> {
+        let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+        if file.metadata()?.len() == 0 {
+            writeln!(file, "timestamp,end,timer,event,elapsed_secs")?;
+        }
+        return Ok(Self { file });
+    }
+    pub fn write(&mut self, end: usize, timer: &str, event: &str, elapsed: Duration) {
+        let timestamp = chrono::Local::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, false);
+        let result = writeln!(
+            self.file,
+            "{timestamp},{end},{timer},{event},{:.3}",
+            elapsed.as_secs_f64()
+        );
+        if let Err(err) = result {
+            log::error!("Failed to write event log: {err}");
+        }
+    }
+    /// Makes sure everything written so far is on disk
+    pub fn sync(&self) {
+        if let Err(err) = self.file.sync_all() {
+            log::error!("Failed to sync event log: {err}");
+        }
+    }
+}
@@ -0,0 +1,192 @@
+use std::{
+    fs::{File, OpenOptions},
+    io::{self, Read, Write},
+    os::fd::AsRawFd,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use futures::channel::mpsc;
+use serde::{Deserialize, Serialize};
+
+use crate::{Bias, ButtonSide, InputLine};
+
+// From linux/i2c-dev.h: sets the address later reads and writes go to
+const I2C_SLAVE: libc::c_ulong = 0x0703;
+
+// MCP23017 registers, with the default IOCON.BANK = 0 layout. Each has an A register for
+// pins 0-7 followed by a B register for pins 8-15.
+const MCP23017_IODIR: u8 = 0x00;
+const MCP23017_GPPU: u8 = 0x0C;
+const MCP23017_GPIO: u8 = 0x12;
+const MCP23017_OLAT: u8 = 0x14;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ExpanderKind {
+    Mcp23017,
+    Pcf8574,
+}
+
+fn default_bus() -> PathBuf {
+    return PathBuf::from("/dev/i2c-1");
+}
+fn default_address() -> u16 {
+    return 0x20;
+}
+fn default_poll_ms() -> u64 {
+    return 10;
+}
+
+/// An I2C expander the buttons and indicators are wired to instead of the Pi's header, with
+/// line offsets counting its pins from 0
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ExpanderConfig {
+    pub kind: ExpanderKind,
+    #[serde(default = "default_bus")]
+    pub bus: PathBuf,
+    #[serde(default = "default_address")]
+    pub address: u16,
+    /// How often the buttons are read
+    #[serde(default = "default_poll_ms")]
+    pub poll_ms: u64,
+}
+
+pub struct Expander {
+    kind: ExpanderKind,
+    file: Mutex<File>,
+    // Pins driven as outputs, and the levels last written to them
+    output_mask: u16,
+    outputs: Mutex<u16>,
+}
+impl Expander {
+    /// Opens the expander, setting up `inputs` as buttons and `output_pins` as outputs
+    pub fn open(
+        config: &ExpanderConfig,
+        inputs: &[InputLine],
+        output_pins: &[u32],
+    ) -> io::Result<Arc<Self>> {
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(&config.bus)?;
+        // SAFETY: I2C_SLAVE takes the address by value and doesn't touch our memory
+        let result = unsafe {
+            libc::ioctl(
+                file.as_raw_fd(),
+                I2C_SLAVE,
+                libc::c_ulong::from(config.address),
+            )
+        };
+        if result < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        let output_mask = output_pins.iter().fold(0u16, |mask, pin| mask | 1 << pin);
+        let expander = Self {
+            kind: config.kind,
+            file: Mutex::new(file),
+            output_mask,
+            outputs: Mutex::new(0),
+        };
+        match config.kind {
+            ExpanderKind::Mcp23017 => {
+                // Pull-ups are the only bias the chip has, and active-low buttons need them
+                let pull_ups = inputs
+                    .iter()
+                    .filter(|input| {
+                        input.bias() == Bias::PullUp
+                            || (input.bias() == Bias::AsIs && input.active_low())
+                    })
+                    .fold(0u16, |mask, input| mask | 1 << input.offset());
+                expander.write_pair(MCP23017_IODIR, !output_mask)?;
+                expander.write_pair(MCP23017_GPPU, pull_ups)?;
+                expander.write_pair(MCP23017_OLAT, 0)?;
+            }
+            // Quasi-bidirectional pins read as inputs while they are written high
+            ExpanderKind::Pcf8574 => expander.write_pins(0)?,
+        }
+        return Ok(Arc::new(expander));
+    }
+    fn write_pair(&self, register: u8, value: u16) -> io::Result<()> {
+        let [low, high] = value.to_le_bytes();
+        return self.file.lock().unwrap().write_all(&[register, low, high]);
+    }
+    fn write_pins(&self, outputs: u16) -> io::Result<()> {
+        return match self.kind {
+            ExpanderKind::Mcp23017 => self.write_pair(MCP23017_OLAT, outputs),
+            ExpanderKind::Pcf8574 => {
+                let byte = (!self.output_mask | outputs) as u8;
+                self.file.lock().unwrap().write_all(&[byte])
+            }
+        };
+    }
+    /// Levels of all pins, pin 0 in the lowest bit
+    pub fn read(&self) -> io::Result<u16> {
+        let mut file = self.file.lock().unwrap();
+        return match self.kind {
+            ExpanderKind::Mcp23017 => {
+                let mut levels = [0; 2];
+                file.write_all(&[MCP23017_GPIO])?;
+                file.read_exact(&mut levels)?;
+                Ok(u16::from_le_bytes(levels))
+            }
+            ExpanderKind::Pcf8574 => {
+                let mut levels = [0; 1];
+                file.read_exact(&mut levels)?;
+                Ok(u16::from(levels[0]))
+            }
+        };
+    }
+    pub fn set(&self, pin: u32, on: bool) -> io::Result<()> {
+        let mut outputs = self.outputs.lock().unwrap();
+        let next = match on {
+            true => *outputs | 1 << pin,
+            false => *outputs & !(1 << pin),
+        };
+        if next != *outputs {
+            self.write_pins(next)?;
+            *outputs = next;
+        }
+        return Ok(());
+    }
+}
+
+/// Polls the buttons on their own thread, passing on the button and whether it's now pressed
+/// whenever one changes
+pub fn watch_buttons(
+    expander: Arc<Expander>,
+    inputs: Vec<(InputLine, ButtonSide)>,
+    poll: Duration,
+) -> mpsc::UnboundedReceiver<(ButtonSide, bool)> {
+    let (sender, receiver) = mpsc::unbounded();
+    std::thread::spawn(move || {
+        let mut pressed = vec![false; inputs.len()];
+        let mut failing = false;
+        loop {
+            match expander.read() {
+                Ok(levels) => {
+                    failing = false;
+                    for ((input, side), pressed) in inputs.iter().zip(&mut pressed) {
+                        let high = levels & 1 << input.offset() != 0;
+                        let now_pressed = high != input.active_low();
+                        if now_pressed != *pressed {
+                            *pressed = now_pressed;
+                            if sender.unbounded_send((*side, now_pressed)).is_err() {
+                                return;
+                            }
+                        }
+                    }
+                }
+                Err(err) => {
+                    if !failing {
+                        log::warn!("Failed to read the GPIO expander: {err}");
+                    }
+                    failing = true;
+                }
+            }
+            std::thread::sleep(poll);
+        }
+    });
+    return receiver;
+}
@@ -0,0 +1,132 @@
+use std::{cell::Cell, fmt, rc::Rc};
+
+use gtk::prelude::*;
+use serde::{Deserialize, Serialize};
+
+// Size of the clocks relative to the window's text, matching `label` in main.css
+const CLOCK_EM: f64 = 4.0;
+// Limits on fitting text to the window, so a window that grows with its text can't keep
+// growing it
+const MIN_FIT: f64 = 0.25;
+const MAX_FIT: f64 = 4.0;
+
+fn default_scale() -> f64 {
+    return 1.0;
+}
+
+/// CSS font weight, either named ("bold") or numeric (700)
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(untagged)]
+pub enum FontWeight {
+    Numeric(u16),
+    Named(String),
+}
+impl fmt::Display for FontWeight {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return match self {
+            FontWeight::Numeric(weight) => write!(f, "{weight}"),
+            FontWeight::Named(weight) => write!(f, "{weight}"),
+        };
+    }
+}
+
+/// Typeface and sizing of everything shown in the main window
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct FontConfig {
+    /// Font family, e.g. "DejaVu Sans Mono"
+    pub family: Option<String>,
+    pub weight: Option<FontWeight>,
+    /// Multiplies the size of all text
+    #[serde(default = "default_scale")]
+    pub scale: f64,
+    /// Multiplies the size of the clocks on top of `scale`
+    #[serde(default = "default_scale")]
+    pub digit_scale: f64,
+    /// Window width and height the sizes are meant for. Text is grown or shrunk to fit the
+    /// actual window as if it were this size.
+    pub reference_size: Option<(u32, u32)>,
+}
+impl Default for FontConfig {
+    fn default() -> Self {
+        return Self {
+            family: None,
+            weight: None,
+            scale: default_scale(),
+            digit_scale: default_scale(),
+            reference_size: None,
+        };
+    }
+}
+impl FontConfig {
+    /// How much the text is scaled for a window of the given size
+    fn fit(&self, width: i32, height: i32) -> f64 {
+        let Some((reference_width, reference_height)) = self.reference_size else {
+            return 1.0;
+        };
+        let fit = f64::min(
+            width as f64 / reference_width.max(1) as f64,
+            height as f64 / reference_height.max(1) as f64,
+        );
+        return fit.clamp(MIN_FIT, MAX_FIT);
+    }
+}
+
+/// Applies the configured font to the window, resizing its text along with the window
+pub struct FontStyle {
+    config: FontConfig,
+    provider: gtk::CssProvider,
+    // Last applied size as a percentage of the default, to avoid reloading the CSS on every
+    // allocation
+    percent: Cell<u32>,
+}
+impl FontStyle {
+    pub fn attach(window: &gtk::Window, clocks: &[&gtk::Label], config: FontConfig) {
+        if config.digit_scale != 1.0 {
+            let clock_provider = gtk::CssProvider::new();
+            clock_provider
+                .load_from_data(
+                    format!("* {{ font-size: {}em; }}", CLOCK_EM * config.digit_scale).as_bytes(),
+                )
+                .unwrap();
+            for clock in clocks {
+                clock
+                    .style_context()
+                    .add_provider(&clock_provider, gtk::STYLE_PROVIDER_PRIORITY_APPLICATION);
+            }
+        }
+        let style = Rc::new(Self {
+            config,
+            provider: gtk::CssProvider::new(),
+            percent: Cell::new(0),
+        });
+        // Text everywhere else is sized in em, so it all follows the window's font size
+        window
+            .style_context()
+            .add_provider(&style.provider, gtk::STYLE_PROVIDER_PRIORITY_APPLICATION);
+        style.apply(1.0);
+        window.connect_size_allocate(move |_, allocation| {
+            style.resize(allocation.width(), allocation.height());
+        });
+    }
+    fn resize(&self, width: i32, height: i32) {
+        self.apply(self.config.fit(width, height));
+    }
+    fn apply(&self, fit: f64) {
+        let percent = (self.config.scale * fit * 100.0).round() as u32;
+        if percent == self.percent.get() {
+            return;
+        }
+        self.percent.set(percent);
+        let mut css = format!("* {{ font-size: {percent}%;");
+        if let Some(ref family) = self.config.family {
+            css.push_str(&format!(" font-family: \"{family}\";"));
+        }
+        if let Some(ref weight) = self.config.weight {
+            css.push_str(&format!(" font-weight: {weight};"));
+        }
+        css.push_str(" }");
+        if let Err(err) = self.provider.load_from_data(css.as_bytes()) {
+            log::error!("Couldn't apply font: {err}");
+        }
+    }
+}
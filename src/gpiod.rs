@@ -0,0 +1,195 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use futures::{channel::mpsc, StreamExt};
+use libgpiod::{line, request};
+
+use crate::{Bias, ButtonSide, ButtonStream, Edges, InputLine};
+
+/// The kernel debounces the lines itself, given each button's `debounce_ms`
+pub const DEBOUNCES_IN_KERNEL: bool = true;
+
+/// A GPIO chip, reached through the libgpiod v2 API
+pub struct Chip {
+    chip: libgpiod::chip::Chip,
+    path: PathBuf,
+}
+impl Chip {
+    /// Opens a GPIO chip by path, or by its label or name
+    pub fn open(chip: &Path) -> Result<Self, String> {
+        if chip.is_absolute() {
+            return Ok(Self {
+                chip: libgpiod::chip::Chip::open(&chip)
+                    .map_err(|err| format!("failed to open {chip:?}: {err}"))?,
+                path: chip.to_owned(),
+            });
+        }
+        let devices =
+            std::fs::read_dir("/dev").map_err(|err| format!("failed to list GPIO chips: {err}"))?;
+        for path in devices.flatten().map(|device| device.path()) {
+            let is_chip = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with("gpiochip"));
+            if !is_chip {
+                continue;
+            }
+            let Ok(found) = libgpiod::chip::Chip::open(&path) else {
+                continue;
+            };
+            let Ok(info) = found.info() else {
+                continue;
+            };
+            let matches = [info.label(), info.name()]
+                .into_iter()
+                .flatten()
+                .any(|name| chip == Path::new(name));
+            if matches {
+                return Ok(Self { chip: found, path });
+            }
+        }
+        return Err(format!("there is no GPIO chip labelled {chip:?}"));
+    }
+    /// Offsets of the lines the kernel has named
+    pub fn line_names(&self) -> Result<HashMap<String, u32>, String> {
+        let lines = self
+            .chip
+            .info()
+            .map_err(|err| format!("failed to read the GPIO chip: {err}"))?
+            .num_lines();
+        let mut names = HashMap::new();
+        for offset in 0..lines as u32 {
+            let info = self
+                .chip
+                .line_info(offset)
+                .map_err(|err| format!("failed to read line {offset}: {err}"))?;
+            if let Ok(name) = info.name() {
+                names.insert(name.to_owned(), offset);
+            }
+        }
+        return Ok(names);
+    }
+    /// Requests a line as an output, starting off
+    pub fn request_output(&mut self, offset: u32, consumer: &str) -> Result<Output, String> {
+        let request = || -> libgpiod::Result<request::Request> {
+            let mut settings = line::Settings::new()?;
+            settings
+                .set_direction(line::Direction::Output)?
+                .set_output_value(line::Value::InActive)?;
+            let mut line_config = line::Config::new()?;
+            line_config.add_line_settings(&[offset], settings)?;
+            let mut request_config = request::Config::new()?;
+            request_config.set_consumer(consumer)?;
+            return self.chip.request_lines(Some(&request_config), &line_config);
+        };
+        let request = request().map_err(|err| err.to_string())?;
+        return Ok(Output {
+            request: Mutex::new(request),
+            offset,
+        });
+    }
+    /// Watches the buttons, with the lines biased and debounced in the kernel. Events are read
+    /// on their own thread and passed on as the button and whether it's now pressed, ending
+    /// with an error if reading fails.
+    pub fn watch_buttons(
+        &mut self,
+        inputs: Vec<(InputLine, ButtonSide, Edges)>,
+    ) -> Result<ButtonStream, String> {
+        let mut sides = HashMap::new();
+        let request = || -> libgpiod::Result<request::Request> {
+            let mut line_config = line::Config::new()?;
+            for (input, side, edges) in inputs {
+                let edge = match edges {
+                    Edges::Both => line::Edge::Both,
+                    Edges::Rising => line::Edge::Rising,
+                };
+                let bias = match input.bias() {
+                    Bias::AsIs => None,
+                    Bias::PullUp => Some(line::Bias::PullUp),
+                    Bias::PullDown => Some(line::Bias::PullDown),
+                    Bias::Disabled => Some(line::Bias::Disabled),
+                };
+                let mut settings = line::Settings::new()?;
+                settings
+                    .set_direction(line::Direction::Input)?
+                    .set_edge_detection(Some(edge))?
+                    .set_bias(bias)?
+                    .set_active_low(input.active_low());
+                if let Some(debounce) = input.debounce() {
+                    settings.set_debounce_period(debounce);
+                }
+                line_config.add_line_settings(&[input.offset()], settings)?;
+                sides.insert(input.offset(), side);
+            }
+            let mut request_config = request::Config::new()?;
+            request_config.set_consumer("read-input")?;
+            return self.chip.request_lines(Some(&request_config), &line_config);
+        };
+        let request = request().map_err(|err| {
+            format!(
+                "failed to request the button lines from {:?}: {err}",
+                self.path
+            )
+        })?;
+
+        let (sender, receiver) = mpsc::unbounded();
+        std::thread::spawn(move || {
+            let mut buffer = match request::Buffer::new(16) {
+                Ok(buffer) => buffer,
+                Err(err) => {
+                    let _ = sender
+                        .unbounded_send(Err(format!("failed to set up for button events: {err}")));
+                    return;
+                }
+            };
+            loop {
+                let events = match request.read_edge_events(&mut buffer) {
+                    Ok(events) => events,
+                    Err(err) => {
+                        let _ = sender
+                            .unbounded_send(Err(format!("failed to read button events: {err}")));
+                        return;
+                    }
+                };
+                for event in events.flatten() {
+                    let Some(&side) = sides.get(&event.line_offset()) else {
+                        continue;
+                    };
+                    let pressed = matches!(event.event_type(), Ok(line::EdgeKind::Rising));
+                    log::debug!(
+                        "{side:?} button edge at {:?} (kernel time)",
+                        event.timestamp()
+                    );
+                    if sender.unbounded_send(Ok((side, pressed))).is_err() {
+                        return;
+                    }
+                }
+            }
+        });
+        return Ok(receiver.boxed_local());
+    }
+}
+
+/// A line requested as an output
+pub struct Output {
+    request: Mutex<request::Request>,
+    offset: u32,
+}
+impl Output {
+    pub fn set(&self, on: bool) -> Result<(), String> {
+        let value = match on {
+            true => line::Value::Active,
+            false => line::Value::InActive,
+        };
+        return self
+            .request
+            .lock()
+            .unwrap()
+            .set_value(self.offset, value)
+            .map(|_| ())
+            .map_err(|err| err.to_string());
+    }
+}
@@ -0,0 +1,132 @@
+use std::collections::HashMap;
+
+use gdk::keys::Key;
+use serde::{Deserialize, Serialize};
+
+/// Something a key can be bound to
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    StartLeft,
+    StartRight,
+    /// Starts the game timer, or freezes everything if it's running
+    Game,
+    Reset,
+    Hold,
+    ExtraTime,
+    NextEnd,
+    PreviousEnd,
+    CycleRound,
+    CycleLeftPreset,
+    CycleRightPreset,
+    CycleLeftArcher,
+    CycleRightArcher,
+    CycleProfile,
+    Split,
+    ToggleStats,
+    SwitchScoringSide,
+    UndoArrow,
+    Schedule,
+    Settings,
+    SaveChanges,
+    SaveReport,
+    DismissSummary,
+    VolumeUp,
+    VolumeDown,
+    ToggleMetronome,
+    /// Pauses or resumes the music, leaving the timers running
+    ToggleMusic,
+    SkipTrack,
+    ToggleFullscreen,
+}
+
+/// One key name or a list of them
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum KeyNames {
+    One(String),
+    Many(Vec<String>),
+}
+impl KeyNames {
+    pub fn names(&self) -> &[String] {
+        return match self {
+            KeyNames::One(name) => std::slice::from_ref(name),
+            KeyNames::Many(names) => names,
+        };
+    }
+}
+
+const DEFAULT_BINDINGS: [(Action, &[&str]); 29] = [
+    (Action::StartLeft, &["j"]),
+    (Action::StartRight, &["k"]),
+    (Action::Game, &["g"]),
+    (Action::Reset, &["r"]),
+    (Action::Hold, &["h"]),
+    (Action::ExtraTime, &["e"]),
+    (Action::NextEnd, &["n"]),
+    (Action::PreviousEnd, &["p"]),
+    (Action::CycleRound, &["t"]),
+    (Action::CycleLeftPreset, &["bracketleft"]),
+    (Action::CycleRightPreset, &["bracketright"]),
+    (Action::CycleLeftArcher, &["J"]),
+    (Action::CycleRightArcher, &["K"]),
+    (Action::CycleProfile, &["P"]),
+    (Action::Split, &["l"]),
+    (Action::ToggleStats, &["i"]),
+    (Action::SwitchScoringSide, &["Tab", "Return", "KP_Enter"]),
+    (Action::UndoArrow, &["BackSpace"]),
+    (Action::Schedule, &["s"]),
+    (Action::Settings, &["c"]),
+    (Action::SaveChanges, &["S"]),
+    (Action::SaveReport, &["w"]),
+    (Action::DismissSummary, &["Escape"]),
+    (Action::VolumeUp, &["plus", "equal", "KP_Add"]),
+    (Action::VolumeDown, &["minus", "KP_Subtract"]),
+    (Action::ToggleMetronome, &["b"]),
+    (
+        Action::ToggleMusic,
+        &["F7", "XF86AudioPlay", "XF86AudioPause"],
+    ),
+    (Action::SkipTrack, &["F8", "XF86AudioNext"]),
+    (Action::ToggleFullscreen, &["F11"]),
+];
+
+/// Looks up a key by its GDK name, e.g. `r`, `Page_Down` or `F5`
+pub fn parse_key(name: &str) -> Option<Key> {
+    let key = Key::from_name(name);
+    return (key != gdk::keys::constants::VoidSymbol).then_some(key);
+}
+
+/// Which action each key triggers
+pub struct Keymap {
+    actions: HashMap<Key, Action>,
+}
+impl Keymap {
+    /// The default bindings, with each configured action's keys replacing its defaults
+    pub fn new(config: &HashMap<Action, KeyNames>) -> Self {
+        let mut actions = HashMap::new();
+        for (action, names) in DEFAULT_BINDINGS {
+            if config.contains_key(&action) {
+                continue;
+            }
+            for key in names.iter().filter_map(|name| parse_key(name)) {
+                actions.insert(key, action);
+            }
+        }
+        // Applied last so a configured key wins over a default using the same key
+        for (action, names) in config {
+            for name in names.names() {
+                match parse_key(name) {
+                    Some(key) => {
+                        actions.insert(key, *action);
+                    }
+                    None => log::warn!("Ignoring unknown key {name:?} bound to {action:?}"),
+                }
+            }
+        }
+        return Self { actions };
+    }
+    pub fn action(&self, key: Key) -> Option<Action> {
+        return self.actions.get(&key).copied();
+    }
+}
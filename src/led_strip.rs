@@ -0,0 +1,108 @@
+use std::{
+    fs::{File, OpenOptions},
+    io::{self, Write},
+    os::fd::AsRawFd,
+    path::PathBuf,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::Light;
+
+// From linux/spi/spidev.h: sets the clock later writes are sent at
+const SPI_IOC_WR_MAX_SPEED_HZ: libc::c_ulong = 0x4004_6B04;
+// Each WS2812 bit is sent as three SPI bits, 100 for a 0 and 110 for a 1, which puts the
+// pulses within the chip's timing at this clock
+const SPI_SPEED_HZ: u32 = 2_400_000;
+// Low time after a frame that latches it, comfortably over the 280us newer chips need
+const RESET_BYTES: usize = 90;
+
+fn default_device() -> PathBuf {
+    return PathBuf::from("/dev/spidev0.0");
+}
+fn default_brightness() -> f64 {
+    return 0.5;
+}
+
+/// WS2812 (NeoPixel) strip on the SPI MOSI pin showing the time left as a bar. The spidev
+/// buffer limits it to about 450 LEDs unless `spidev.bufsiz` is raised.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct LedStripConfig {
+    #[serde(default = "default_device")]
+    pub device: PathBuf,
+    pub leds: usize,
+    /// Scales every color, from 0 to 1, since full white draws a lot of current
+    #[serde(default = "default_brightness")]
+    pub brightness: f64,
+    /// Empties the bar towards the first LED instead of the last
+    #[serde(default)]
+    pub reversed: bool,
+}
+
+pub struct LedStrip {
+    file: File,
+    config: LedStripConfig,
+    // Last frame sent, so an unchanged bar isn't sent again
+    last_frame: Vec<u8>,
+}
+impl LedStrip {
+    pub fn open(config: &LedStripConfig) -> io::Result<Self> {
+        let file = OpenOptions::new().write(true).open(&config.device)?;
+        let speed = SPI_SPEED_HZ;
+        // SAFETY: the speed is only read through the pointer, during the call
+        let result = unsafe { libc::ioctl(file.as_raw_fd(), SPI_IOC_WR_MAX_SPEED_HZ, &speed) };
+        if result < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        return Ok(Self {
+            file,
+            config: config.clone(),
+            last_frame: Vec::new(),
+        });
+    }
+    /// Lights `remaining` (0 to 1) of the strip in the color of the current phase
+    pub fn show(&mut self, remaining: f64, light: Light) -> io::Result<()> {
+        let (red, green, blue) = match light {
+            Light::Red => (255, 0, 0),
+            Light::Amber => (255, 120, 0),
+            Light::Green => (0, 255, 0),
+        };
+        let scale = |level: u8| (f64::from(level) * self.config.brightness.clamp(0.0, 1.0)) as u8;
+        let lit = (remaining.clamp(0.0, 1.0) * self.config.leds as f64).ceil() as usize;
+        let mut frame = Vec::with_capacity(self.config.leds * 9 + RESET_BYTES);
+        for i in 0..self.config.leds {
+            let on = match self.config.reversed {
+                true => i >= self.config.leds - lit,
+                false => i < lit,
+            };
+            // WS2812s take green first
+            let color = match on {
+                true => [scale(green), scale(red), scale(blue)],
+                false => [0; 3],
+            };
+            for level in color {
+                frame.extend(encode(level));
+            }
+        }
+        frame.resize(frame.len() + RESET_BYTES, 0);
+        if frame != self.last_frame {
+            self.file.write_all(&frame)?;
+            self.last_frame = frame;
+        }
+        return Ok(());
+    }
+}
+
+/// Spreads a byte over the 24 SPI bits that clock it out to the strip
+fn encode(level: u8) -> [u8; 3] {
+    let mut bits = 0u32;
+    for i in (0..8).rev() {
+        bits = bits << 3
+            | match level >> i & 1 {
+                1 => 0b110,
+                _ => 0b100,
+            };
+    }
+    let [_, high, middle, low] = bits.to_be_bytes();
+    return [high, middle, low];
+}
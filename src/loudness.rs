@@ -0,0 +1,64 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+use crate::stream;
+
+// Level tracks are brought to, as RMS in dB below full scale, roughly where ReplayGain puts
+// its reference level
+const TARGET_DB: f32 = -18.0;
+// Most a quiet track is boosted by, so near-silent tracks don't turn into hiss
+const MAX_BOOST_DB: f32 = 12.0;
+
+/// Gains that bring each music track to the same loudness, measured in the background
+#[derive(Clone, Default)]
+pub struct Loudness {
+    gains: Arc<Mutex<HashMap<PathBuf, f32>>>,
+}
+impl Loudness {
+    /// Gain for a track, or 1 if it hasn't been measured (yet)
+    pub fn gain(&self, path: &Path) -> f32 {
+        return self.gains.lock().unwrap().get(path).copied().unwrap_or(1.0);
+    }
+    /// Measures the tracks that haven't been already, on a thread of its own since decoding
+    /// a whole library takes a while on slow boards
+    pub fn analyze(&self, paths: Vec<PathBuf>) {
+        let gains = Arc::clone(&self.gains);
+        std::thread::spawn(move || {
+            for path in paths {
+                if stream::is_stream(&path) || gains.lock().unwrap().contains_key(&path) {
+                    continue;
+                }
+                match measure(&path) {
+                    Ok(gain) => {
+                        log::debug!("Normalizing {path:?} by {:+.1} dB", 20.0 * gain.log10());
+                        gains.lock().unwrap().insert(path, gain);
+                    }
+                    Err(err) => log::warn!("Couldn't measure the loudness of {path:?}: {err}"),
+                }
+            }
+        });
+    }
+}
+
+/// Decodes a whole track to find the gain that brings it to the target level without clipping
+fn measure(path: &Path) -> Result<f32, String> {
+    let file = std::fs::File::open(path).map_err(|err| err.to_string())?;
+    let decoder = rodio::Decoder::try_from(file).map_err(|err| err.to_string())?;
+    let mut sum_squares = 0.0f64;
+    let mut samples = 0u64;
+    let mut peak = 0.0f32;
+    for sample in decoder {
+        sum_squares += f64::from(sample) * f64::from(sample);
+        samples += 1;
+        peak = peak.max(sample.abs());
+    }
+    if samples == 0 || peak == 0.0 {
+        return Ok(1.0);
+    }
+    let rms_db = 10.0 * (sum_squares / samples as f64).log10() as f32;
+    let gain_db = (TARGET_DB - rms_db).min(MAX_BOOST_DB);
+    return Ok(10f32.powf(gain_db / 20.0).min(1.0 / peak));
+}
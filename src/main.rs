@@ -1,20 +1,99 @@
+mod buzzer;
+#[cfg(feature = "legacy-cdev")]
+mod cdev;
+mod checkpoint;
+mod config_file;
+mod evdev;
+mod event_log;
+mod expander;
+mod font;
+#[cfg(all(feature = "gpiod", not(feature = "legacy-cdev")))]
+mod gpiod;
+mod keybindings;
+mod led_strip;
+mod loudness;
+#[cfg(not(any(feature = "gpiod", feature = "legacy-cdev")))]
+mod no_gpio;
+mod participants;
+mod playlist;
+mod report;
+mod roster;
+mod rotation;
+mod scoring;
+mod serial;
+mod session;
+mod settings;
+mod seven_segment;
+mod simulator;
+mod speech;
+#[cfg(feature = "sqlite")]
+mod storage;
+mod stream;
+mod theme;
+mod ticker;
+mod time_format;
+mod validation;
+mod voice_pack;
+
 use std::{
+    cell::RefCell,
+    collections::HashMap,
+    fmt,
     future::Future,
+    io::Write,
     path::{Path, PathBuf},
     pin::Pin,
-    sync::{Arc, Mutex},
+    rc::Rc,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
     time::Duration,
 };
 
+use clap::Parser;
 use gdk::EventMask;
 use gio::prelude::*;
-use gpio_cdev::{AsyncLineEventHandle, Chip, EventRequestFlags, LineRequestFlags};
 use gtk::prelude::*;
 
+use buzzer::Buzzer;
+use checkpoint::{Checkpoint, TimerCheckpoint};
+use config_file::{ConfigSource, SettingChange};
+use evdev::InputDeviceConfig;
+use event_log::EventLog;
+use expander::{Expander, ExpanderConfig};
+// The GPIO backend: libgpiod by default, gpio-cdev on older systems, or none at all
+#[cfg(feature = "legacy-cdev")]
+use cdev as gpio_chip;
+use font::{FontConfig, FontStyle};
 use futures::StreamExt;
-use rodio::Source;
+use gpio_chip::Chip;
+#[cfg(all(feature = "gpiod", not(feature = "legacy-cdev")))]
+use gpiod as gpio_chip;
+use keybindings::{Action, KeyNames, Keymap};
+use led_strip::{LedStrip, LedStripConfig};
+use loudness::Loudness;
+#[cfg(not(any(feature = "gpiod", feature = "legacy-cdev")))]
+use no_gpio as gpio_chip;
+use participants::Participant;
+use playlist::{MusicSource, Playlist};
+use rand::Rng;
+use rodio::{
+    cpal::traits::{DeviceTrait, HostTrait},
+    Source,
+};
+use roster::Archer;
+use scoring::{Arrow, Scoreboard, SetStandings, SideScore};
 use serde::{Deserialize, Serialize};
+use serial::SerialConfig;
+use session::{EndRecord, FinishedSession, Session, SideStats};
+use seven_segment::{SegmentTimer, SevenSegment, SevenSegmentConfig};
+use speech::{SpeechConfig, Utterance};
+use theme::Theme;
+use ticker::{Ticker, TickerConfig};
+use time_format::TimeFormat;
 use tokio::time::{Instant, Sleep};
+use voice_pack::VoicePack;
 
 struct Stopwatch {
     start_time: Option<Instant>,
@@ -54,10 +133,16 @@ impl Stopwatch {
             None => self.offset,
         };
     }
+    pub fn restore(&mut self, elapsed: Duration, running: bool) {
+        self.offset = elapsed;
+        self.start_time = running.then(Instant::now);
+    }
 }
 
 struct Timer {
     duration: Duration,
+    // Time granted on top of the duration, until the timer is cleared
+    extra: Duration,
     start_time: Option<Instant>,
     offset: Duration,
 }
@@ -65,6 +150,7 @@ impl Timer {
     pub fn new(duration: Duration) -> Self {
         return Self {
             duration,
+            extra: Duration::ZERO,
             start_time: None,
             offset: Duration::new(0, 0),
         };
@@ -89,321 +175,5325 @@ impl Timer {
     pub fn clear(&mut self) {
         self.start_time = None;
         self.offset = Duration::from_secs(0);
+        self.extra = Duration::ZERO;
     }
     pub fn get_remaining(&self) -> Duration {
-        let offset_remaining = self.duration.saturating_sub(self.offset);
+        let offset_remaining = (self.duration + self.extra).saturating_sub(self.offset);
         return match self.start_time {
             Some(start_time) => offset_remaining.saturating_sub(start_time.elapsed()),
             None => offset_remaining,
         };
     }
+    pub fn get_duration(&self) -> Duration {
+        return self.duration;
+    }
+    pub fn set_duration(&mut self, duration: Duration) {
+        self.duration = duration;
+    }
+    pub fn restore(&mut self, elapsed: Duration, running: bool) {
+        self.offset = elapsed;
+        self.start_time = running.then(Instant::now);
+    }
+    /// Lengthens the timer until it's next cleared, e.g. to grant extra time
+    pub fn extend(&mut self, extra: Duration) {
+        self.extra += extra;
+    }
+    pub fn get_extra(&self) -> Duration {
+        return self.extra;
+    }
+    pub fn get_elapsed(&self) -> Duration {
+        return match self.start_time {
+            Some(start_time) => self.offset + start_time.elapsed(),
+            None => self.offset,
+        };
+    }
+    /// How far the timer has run past zero
+    pub fn get_overtime(&self) -> Duration {
+        return self
+            .get_elapsed()
+            .saturating_sub(self.duration + self.extra);
+    }
+}
+
+/// Follows a countdown from tick to tick, to spot the times it counts down past
+#[derive(Default)]
+struct CountdownWatch {
+    // Remaining time at the last update, and when that was
+    last: Option<(Duration, Instant)>,
+}
+impl CountdownWatch {
+    /// Records the remaining time, returning the remaining time it has counted down from since
+    /// the last update. Jumps faster than the clock, such as the timer being cleared or
+    /// swapped for the next phase's, are ignored.
+    pub fn update(&mut self, remaining: Duration) -> Option<Duration> {
+        let last = self.last.replace((remaining, Instant::now()));
+        let (previous, updated) = last?;
+        if previous <= remaining || previous - remaining > updated.elapsed() + WATCH_SLACK {
+            return None;
+        }
+        return Some(previous);
+    }
+}
+// Allowance for timers and ticks not reading the clock at quite the same moment
+const WATCH_SLACK: Duration = Duration::from_millis(100);
+
+enum SideTimer {
+    Stopwatch(Stopwatch),
+    /// With `overtime`, the countdown keeps running past zero instead of being stopped
+    Countdown {
+        timer: Timer,
+        overtime: bool,
+    },
+}
+impl SideTimer {
+    pub fn new(config: &TimerConfig) -> Self {
+        return match config.mode {
+            TimerMode::Stopwatch => SideTimer::Stopwatch(Stopwatch::new()),
+            TimerMode::Countdown => SideTimer::Countdown {
+                timer: Timer::new(Duration::from_secs(config.time_secs)),
+                overtime: config.overtime,
+            },
+        };
+    }
+    pub fn is_running(&self) -> bool {
+        return match self {
+            SideTimer::Stopwatch(stopwatch) => stopwatch.is_running(),
+            SideTimer::Countdown { timer, .. } => timer.is_running(),
+        };
+    }
+    pub fn start(&mut self) {
+        match self {
+            SideTimer::Stopwatch(stopwatch) => stopwatch.start(),
+            SideTimer::Countdown { timer, .. } => {
+                // Restart an expired countdown rather than immediately expiring again
+                if timer.get_remaining().is_zero() {
+                    timer.clear();
+                }
+                timer.start();
+            }
+        }
+    }
+    /// Carries on from where the timer was stopped, keeping any time run past zero
+    pub fn resume(&mut self) {
+        match self {
+            SideTimer::Stopwatch(stopwatch) => stopwatch.start(),
+            SideTimer::Countdown { timer, .. } => timer.start(),
+        }
+    }
+    pub fn stop(&mut self) {
+        match self {
+            SideTimer::Stopwatch(stopwatch) => stopwatch.stop(),
+            SideTimer::Countdown { timer, .. } => timer.stop(),
+        }
+    }
+    pub fn clear(&mut self) {
+        match self {
+            SideTimer::Stopwatch(stopwatch) => stopwatch.clear(),
+            SideTimer::Countdown { timer, .. } => timer.clear(),
+        }
+    }
+    pub fn get_elapsed(&self) -> Duration {
+        return match self {
+            SideTimer::Stopwatch(stopwatch) => stopwatch.get_duration(),
+            SideTimer::Countdown { timer, .. } => timer.get_elapsed(),
+        };
+    }
+    /// Elapsed time for stopwatches, remaining time for countdowns
+    pub fn get_display(&self) -> Duration {
+        return match self {
+            SideTimer::Stopwatch(stopwatch) => stopwatch.get_duration(),
+            SideTimer::Countdown { timer, .. } => timer.get_remaining(),
+        };
+    }
+    /// Whether the timer is sitting at its starting point, either cleared or run out
+    pub fn is_fresh(&self) -> bool {
+        return match self {
+            SideTimer::Stopwatch(stopwatch) => {
+                !stopwatch.is_running() && stopwatch.get_duration().is_zero()
+            }
+            SideTimer::Countdown { timer, .. } => {
+                !timer.is_running()
+                    && (timer.get_elapsed().is_zero() || timer.get_remaining().is_zero())
+            }
+        };
+    }
+    pub fn get_countdown(&self) -> Option<Duration> {
+        return match self {
+            SideTimer::Stopwatch(_) => None,
+            SideTimer::Countdown { timer, .. } => Some(timer.get_duration()),
+        };
+    }
+    pub fn set_countdown(&mut self, duration: Duration) {
+        if let SideTimer::Countdown { timer, .. } = self {
+            timer.set_duration(duration);
+        }
+    }
+    pub fn restore(&mut self, elapsed: Duration, running: bool) {
+        match self {
+            SideTimer::Stopwatch(stopwatch) => stopwatch.restore(elapsed, running),
+            SideTimer::Countdown { timer, .. } => timer.restore(elapsed, running),
+        }
+    }
+    /// Adds time to a countdown. Returns false for stopwatches, which have no remaining time.
+    pub fn extend(&mut self, extra: Duration) -> bool {
+        return match self {
+            SideTimer::Stopwatch(_) => false,
+            SideTimer::Countdown { timer, .. } => {
+                timer.extend(extra);
+                true
+            }
+        };
+    }
+    /// Time granted on top of a countdown
+    pub fn get_extra(&self) -> Duration {
+        return match self {
+            SideTimer::Stopwatch(_) => Duration::ZERO,
+            SideTimer::Countdown { timer, .. } => timer.get_extra(),
+        };
+    }
+    pub fn counts_down(&self) -> bool {
+        return matches!(self, SideTimer::Countdown { .. });
+    }
+    pub fn is_expired(&self) -> bool {
+        return match self {
+            SideTimer::Stopwatch(_) => false,
+            SideTimer::Countdown { timer, .. } => timer.get_remaining().is_zero(),
+        };
+    }
+    pub fn allows_overtime(&self) -> bool {
+        return matches!(self, SideTimer::Countdown { overtime: true, .. });
+    }
+    pub fn get_overtime(&self) -> Duration {
+        return match self {
+            SideTimer::Countdown {
+                timer,
+                overtime: true,
+            } => timer.get_overtime(),
+            _ => Duration::ZERO,
+        };
+    }
 }
 
 struct AudioController {
-    output_stream: rodio::OutputStream,
+    // None until an output device can be opened
+    output_stream: Option<rodio::OutputStream>,
+    // Whether the output is the fallback device, while the default one is tried again
+    on_fallback: bool,
     running_player: Option<rodio::Sink>,
+    // Master volume, and the volume of the track playing on top of it
+    volume: f32,
+    track_volume: f32,
+    // Set by the output stream when the device fails, e.g. a USB DAC being unplugged
+    stream_error: Arc<Mutex<Option<String>>>,
+    // Started the first time something is said
+    speaker: Option<std::sync::mpsc::Sender<Utterance>>,
+    // How long music takes to fade out when it's stopped
+    fade_out: Duration,
+    // Latest file that couldn't be played, until it's been reported
+    failure: Arc<Mutex<Option<String>>>,
+    // Click track, and the time between its clicks
+    metronome: Option<(rodio::Sink, Duration)>,
+    loudness: Loudness,
+    // Music paused from the keyboard, which the end of a hold shouldn't resume
+    music_paused: bool,
+    // Cues, horns and beeps playing over the music, and whether something is being said
+    announcements: Vec<rodio::Sink>,
+    speaking: Arc<AtomicBool>,
+    // Music volume while they play, and the level it's currently at
+    duck_level: f32,
+    duck: f32,
+    // Device used when the default output can't be opened
+    fallback_device: Option<String>,
+    // What the running player is playing, so it can be picked back up on a new device
+    current_track: Option<Track>,
+    last_reopen: Option<Instant>,
 }
 impl AudioController {
-    pub fn new() -> Self {
+    pub fn new(
+        volume: f32,
+        fade_out: Duration,
+        duck_level: f32,
+        fallback_device: Option<String>,
+    ) -> Self {
+        let stream_error = Arc::new(Mutex::new(None));
+        // Without any output the timers still run, and `recover` keeps trying
+        let (output_stream, on_fallback) =
+            match open_output(fallback_device.as_deref(), &stream_error) {
+                Ok((output_stream, on_fallback)) => (Some(output_stream), on_fallback),
+                Err(err) => {
+                    log::error!("No audio output: {err}");
+                    *stream_error.lock().unwrap() = Some(err);
+                    (None, false)
+                }
+            };
         return Self {
-            output_stream: rodio::OutputStreamBuilder::open_default_stream().unwrap(),
+            output_stream,
+            on_fallback,
             running_player: None,
+            volume,
+            track_volume: 1.0,
+            stream_error,
+            speaker: None,
+            fade_out,
+            failure: Arc::new(Mutex::new(None)),
+            metronome: None,
+            loudness: Loudness::default(),
+            music_paused: false,
+            announcements: Vec::new(),
+            speaking: Arc::new(AtomicBool::new(false)),
+            duck_level,
+            duck: 1.0,
+            fallback_device,
+            current_track: None,
+            last_reopen: Some(Instant::now()),
+        };
+    }
+    pub fn volume(&self) -> f32 {
+        return self.volume;
+    }
+    pub fn set_volume(&mut self, volume: f32) {
+        self.volume = volume;
+        if let Some(ref player) = self.running_player {
+            player.set_volume(self.player_volume());
+        }
+        if let Some((ref metronome, _)) = self.metronome {
+            metronome.set_volume(self.volume);
+        }
+    }
+    pub fn set_fade_out(&mut self, fade_out: Duration) {
+        self.fade_out = fade_out;
+    }
+    pub fn set_duck_level(&mut self, duck_level: f32) {
+        self.duck_level = duck_level;
+    }
+    pub fn set_fallback_device(&mut self, fallback_device: Option<String>) {
+        self.fallback_device = fallback_device;
+    }
+    /// Reopens the output once it has failed, e.g. when a USB DAC is unplugged, or the default
+    /// device once it's back while the fallback is playing, and picks the music back up where
+    /// it was
+    pub fn recover(&mut self) {
+        let failed = self.stream_error.lock().unwrap().is_some();
+        if !failed && !self.on_fallback {
+            return;
+        }
+        let interval = match failed {
+            true => AUDIO_REOPEN_INTERVAL,
+            false => PREFERRED_OUTPUT_RETRY_INTERVAL,
+        };
+        if let Some(last_reopen) = self.last_reopen {
+            if last_reopen.elapsed() < interval {
+                return;
+            }
+        }
+        self.last_reopen = Some(Instant::now());
+        let opened = match failed {
+            true => open_output(self.fallback_device.as_deref(), &self.stream_error),
+            false => open_default_output(&self.stream_error).map(|stream| (stream, false)),
+        };
+        let (output_stream, on_fallback) = match opened {
+            Ok(opened) => opened,
+            Err(err) => {
+                if failed {
+                    log::warn!("Couldn't reopen audio output: {err}");
+                }
+                return;
+            }
+        };
+        log::info!("Audio output reopened");
+        // Everything playing was tied to the old device
+        self.output_stream = Some(output_stream);
+        self.on_fallback = on_fallback;
+        *self.stream_error.lock().unwrap() = None;
+        self.metronome = None;
+        self.announcements.clear();
+        self.speaker = None;
+        let Some(player) = self.running_player.take() else {
+            return;
+        };
+        let Some(track) = self.current_track.clone().filter(|_| !player.empty()) else {
+            return;
+        };
+        let music_paused = self.music_paused;
+        self.play_track(&track.path, track.volume, track.repeat, track.pan);
+        if let Some(ref new_player) = self.running_player {
+            // Streams can't seek, but carry on live anyway
+            let _ = new_player.try_seek(player.get_pos());
+            if player.is_paused() {
+                new_player.pause();
+            }
+        }
+        self.music_paused = music_paused;
+    }
+    fn player_volume(&self) -> f32 {
+        return self.volume * self.track_volume * self.duck;
+    }
+    /// A new sink on the output, if there is one
+    fn new_sink(&self) -> Option<rodio::Sink> {
+        let output_stream = self.output_stream.as_ref()?;
+        return Some(rodio::Sink::connect_new(output_stream.mixer()));
+    }
+    /// Turns the music down while anything is announced over it, and back up afterwards
+    pub fn update_ducking(&mut self) {
+        self.announcements.retain(|sink| !sink.empty());
+        let announcing = !self.announcements.is_empty() || self.speaking.load(Ordering::Relaxed);
+        let duck = match announcing {
+            true => self.duck_level,
+            false => 1.0,
+        };
+        if duck == self.duck {
+            return;
+        }
+        self.duck = duck;
+        if let Some(ref player) = self.running_player {
+            player.set_volume(self.player_volume());
+        }
+    }
+    /// Plays a sink of its own over the music until it runs out
+    fn announce(&mut self, sink: rodio::Sink) {
+        self.announcements.push(sink);
+        self.update_ducking();
+    }
+    /// Starts measuring tracks so they can be played at the same loudness, or with `None`,
+    /// forgets the measurements and plays tracks as they are
+    pub fn normalize(&mut self, tracks: Option<Vec<PathBuf>>) {
+        match tracks {
+            Some(tracks) => self.loudness.analyze(tracks),
+            None => self.loudness = Loudness::default(),
+        }
+    }
+    /// Takes the latest error from playing a file, so it can be reported once
+    pub fn take_failure(&mut self) -> Option<String> {
+        return self.failure.lock().unwrap().take();
+    }
+    /// Opens a file for playing, logging and recording why if it can't be
+    fn open_file(
+        &mut self,
+        file_path: &Path,
+    ) -> Option<rodio::Decoder<std::io::BufReader<std::fs::File>>> {
+        let decoder = std::fs::File::open(file_path)
+            .map_err(|err| err.to_string())
+            .and_then(|file| rodio::Decoder::try_from(file).map_err(|err| err.to_string()));
+        return match decoder {
+            Ok(decoder) => Some(decoder),
+            Err(err) => {
+                report_failure(&self.failure, file_path, err);
+                None
+            }
         };
     }
+    /// Why audio output stopped working, if it has
+    pub fn fault(&self) -> Option<String> {
+        return self.stream_error.lock().unwrap().clone();
+    }
     pub fn play_file(&mut self, file_path: &Path) {
-        // Drop existing player to make it stop
-        self.running_player.take();
-
-        // Start new player
-        let file = std::fs::File::open(file_path).unwrap();
-        let sink = rodio::Sink::connect_new(self.output_stream.mixer());
-        sink.append(rodio::Decoder::try_from(file).unwrap());
-        self.running_player = Some(sink);
+        self.play_track(file_path, 1.0, false, 0.0);
     }
-    pub fn play_file_loop(&mut self, file_path: &Path) {
-        // Drop existing player to make it stop
-        self.running_player.take();
+    /// Plays a track at `volume` relative to the master volume, looping it if `repeat`. A
+    /// `pan` from -1 (left) to 1 (right) moves it towards one speaker.
+    pub fn play_track(&mut self, file_path: &Path, volume: f32, repeat: bool, pan: f32) {
+        self.fade_player();
 
         // Start new player
-        let file = std::fs::File::open(file_path).unwrap();
-        let sink = rodio::Sink::connect_new(self.output_stream.mixer());
-        sink.append(rodio::Decoder::try_from(file).unwrap().repeat_infinite());
+        self.music_paused = false;
+        self.current_track = Some(Track {
+            path: file_path.to_path_buf(),
+            volume,
+            repeat,
+            pan,
+        });
+        let Some(sink) = self.new_sink() else {
+            return;
+        };
+        self.track_volume = volume * self.loudness.gain(file_path);
+        sink.set_volume(self.player_volume());
+        if stream::is_stream(file_path) {
+            // Connecting can take a while, so it's done off the UI thread, with the player
+            // staying silent until the stream arrives. Streams are never repeated, since
+            // that would keep the whole of them in memory.
+            let (queue, output) = rodio::queue::queue(true);
+            sink.append(output);
+            let url = file_path.to_path_buf();
+            let failure = Arc::clone(&self.failure);
+            std::thread::spawn(move || {
+                match stream::open(&url) {
+                    Ok(decoder) => queue.append(arrange_track(Box::new(decoder), false, pan)),
+                    Err(err) => report_failure(&failure, &url, err),
+                }
+                queue.set_keep_alive_if_empty(false);
+            });
+        } else {
+            let Some(decoder) = self.open_file(file_path) else {
+                return;
+            };
+            sink.append(arrange_track(Box::new(decoder), repeat, pan));
+        }
         self.running_player = Some(sink);
     }
-    pub fn stop(&mut self) {
-        // Drop existing player to make it stop
-        self.running_player.take();
+    /// Plays a short sound once, either over whatever is playing or in place of it
+    pub fn play_cue(&mut self, file_path: &Path, layered: bool) {
+        if !layered {
+            self.play_file(file_path);
+            return;
+        }
+        let Some(sink) = self.new_sink() else {
+            return;
+        };
+        let Some(decoder) = self.open_file(file_path) else {
+            return;
+        };
+        sink.set_volume(self.volume);
+        sink.append(decoder);
+        self.announce(sink);
     }
-}
-
-#[derive(Serialize, Deserialize, Debug, Clone)]
-struct TimerConfig {
-    color: String,
-    text_color: String,
-    music_file: Option<PathBuf>,
-    #[serde(default)]
-    flipped: bool,
-}
-
-#[derive(Serialize, Deserialize, Debug, Clone)]
-struct GameTimerConfig {
-    time_secs: u64,
-    color: String,
-    text_color: String,
-    start_sfx: Option<PathBuf>,
-    end_sfx: Option<PathBuf>,
-}
-
-#[derive(Serialize, Deserialize, Debug, Clone)]
-struct Config {
-    button_toggle: bool,
-    game_timer: GameTimerConfig,
-    left_timer: TimerConfig,
-    right_timer: TimerConfig,
-}
-
-struct ApplicationState {
-    config: Config,
-    game_timer: Timer,
-    left_timer: Stopwatch,
-    right_timer: Stopwatch,
-    audio_controller: AudioController,
-}
-impl ApplicationState {
-    pub fn new(config: Config) -> Self {
-        return Self {
-            game_timer: Timer::new(Duration::from_secs(config.game_timer.time_secs)),
-            left_timer: Stopwatch::new(),
-            right_timer: Stopwatch::new(),
-            audio_controller: AudioController::new(),
-            config,
+    /// Queues text to be spoken by `command` over whatever is playing
+    pub fn speak(&mut self, command: &[String], text: String) {
+        let Some(ref output_stream) = self.output_stream else {
+            return;
         };
+        let speaker = self.speaker.get_or_insert_with(|| {
+            speech::spawn(output_stream.mixer().clone(), Arc::clone(&self.speaking))
+        });
+        let utterance = Utterance {
+            command: command.to_vec(),
+            text,
+            volume: self.volume,
+        };
+        if speaker.send(utterance).is_err() {
+            log::error!("Speech thread has stopped");
+        }
     }
-
-    pub fn clear_timers(&mut self) {
-        self.left_timer.clear();
-        self.right_timer.clear();
-        self.game_timer.clear();
-        self.audio_controller.stop();
+    /// Sounds synthesized horn blasts over whatever is playing
+    pub fn play_horn(&mut self, horn: &HornConfig, blasts: u32) {
+        if blasts == 0 {
+            return;
+        }
+        let Some(sink) = self.new_sink() else {
+            return;
+        };
+        sink.set_volume(self.volume);
+        for blast in 0..blasts {
+            if blast > 0 {
+                sink.append(
+                    rodio::source::Zero::new(1, HORN_SAMPLE_RATE)
+                        .take_duration(Duration::from_millis(horn.gap_ms)),
+                );
+            }
+            // A sawtooth is brassy enough to pass for a horn
+            let mut tone = rodio::source::SawtoothWave::new(horn.frequency_hz)
+                .amplify(0.5)
+                .take_duration(Duration::from_millis(horn.blast_ms));
+            tone.set_filter_fadeout();
+            sink.append(tone.fade_in(TONE_FADE));
+        }
+        self.announce(sink);
     }
-    pub fn start_game_timer(&mut self) {
-        self.clear_timers();
-        if let Some(ref start_sfx) = self.config.game_timer.start_sfx {
-            self.audio_controller.play_file(start_sfx);
-            std::thread::sleep(Duration::from_millis(500));
+    /// Sounds a short synthesized beep over whatever is playing
+    pub fn play_beep(&mut self) {
+        let Some(sink) = self.new_sink() else {
+            return;
+        };
+        sink.set_volume(self.volume);
+        let mut beep = rodio::source::SineWave::new(BEEP_FREQUENCY_HZ)
+            .amplify(0.5)
+            .take_duration(BEEP_LENGTH);
+        beep.set_filter_fadeout();
+        sink.append(beep.fade_in(TONE_FADE));
+        self.announce(sink);
+    }
+    /// Clicks every `interval` over whatever is playing until stopped
+    pub fn start_metronome(&mut self, interval: Duration) {
+        if let Some((_, current)) = self.metronome {
+            if current == interval {
+                return;
+            }
         }
-        self.game_timer.start();
+        let Some(sink) = self.new_sink() else {
+            return;
+        };
+        sink.set_volume(self.volume);
+        sink.append(rodio::source::from_factory(move || {
+            Some(click_track(interval))
+        }));
+        self.metronome = Some((sink, interval));
     }
-    pub fn start_left_timer(&mut self) {
-        if self.left_timer.is_running() && self.config.button_toggle {
-            self.left_timer.stop();
+    pub fn stop_metronome(&mut self) {
+        self.metronome.take();
+    }
+    pub fn stop(&mut self) {
+        self.fade_player();
+    }
+    /// Fades out and drops the running player. Dropping it straight away cuts the music off
+    /// mid-waveform, which pops.
+    fn fade_player(&mut self) {
+        self.current_track = None;
+        let Some(player) = self.running_player.take() else {
+            return;
+        };
+        if self.fade_out.is_zero() || player.is_paused() {
             return;
         }
-        self.right_timer.stop();
-        self.left_timer.start();
-        if let Some(ref music_path) = self.config.left_timer.music_file {
-            self.audio_controller.play_file_loop(music_path);
+        let fade_out = self.fade_out;
+        std::thread::spawn(move || {
+            let start_volume = player.volume();
+            let started = std::time::Instant::now();
+            while started.elapsed() < fade_out {
+                let progress = started.elapsed().as_secs_f32() / fade_out.as_secs_f32();
+                player.set_volume(start_volume * (1.0 - progress));
+                std::thread::sleep(FADE_STEP);
+            }
+        });
+    }
+    pub fn pause(&mut self) {
+        if let Some(ref player) = self.running_player {
+            player.pause();
         }
     }
-    pub fn start_right_timer(&mut self) {
-        if self.right_timer.is_running() && self.config.button_toggle {
-            self.right_timer.stop();
+    pub fn resume(&mut self) {
+        if self.music_paused {
             return;
         }
-        self.left_timer.stop();
-        self.right_timer.start();
-        if let Some(ref music_path) = self.config.right_timer.music_file {
-            self.audio_controller.play_file_loop(music_path);
+        if let Some(ref player) = self.running_player {
+            player.play();
         }
     }
-
-    pub fn freeze(&mut self) {
-        self.game_timer.stop();
-        self.left_timer.stop();
-        self.right_timer.stop();
-        if let Some(ref game_stop_file) = self.config.game_timer.end_sfx {
-            self.audio_controller.play_file(game_stop_file);
-        } else {
-            self.audio_controller.stop();
+    /// Pauses or resumes the music until the next track starts
+    pub fn toggle_music(&mut self) {
+        let Some(ref player) = self.running_player else {
+            return;
+        };
+        self.music_paused = !player.is_paused();
+        match self.music_paused {
+            true => player.pause(),
+            false => player.play(),
         }
     }
 }
 
-fn activate(application: &gtk::Application, timers: Arc<Mutex<ApplicationState>>) {
-    let state = timers.lock().unwrap();
+/// A track as it was asked to be played
+#[derive(Clone)]
+struct Track {
+    path: PathBuf,
+    volume: f32,
+    repeat: bool,
+    pan: f32,
+}
 
-    // Set up the window
-    let window = gtk::ApplicationWindow::new(application);
-    window.style_context().add_class("archery-timer");
-    window.fullscreen();
+// How often to try reopening a failed audio output
+const AUDIO_REOPEN_INTERVAL: Duration = Duration::from_secs(2);
+// How often the default output is tried again while the fallback is playing
+const PREFERRED_OUTPUT_RETRY_INTERVAL: Duration = Duration::from_secs(10);
 
-    // Create basic structure within window
-    let bar = gtk::Box::new(gtk::Orientation::Horizontal, 0);
-    window.set_events(EventMask::KEY_PRESS_MASK);
-    window.set_child(Some(&bar));
+/// Records failures of a running stream in `stream_error`
+fn on_stream_error(
+    stream_error: &Arc<Mutex<Option<String>>>,
+) -> impl FnMut(rodio::cpal::StreamError) + Clone + Send + 'static {
+    let error = Arc::clone(stream_error);
+    return move |err: rodio::cpal::StreamError| {
+        log::error!("Audio output failed: {err}");
+        *error.lock().unwrap() = Some(err.to_string());
+    };
+}
 
-    let left = gtk::Box::new(gtk::Orientation::Horizontal, 0);
-    bar.pack_start(&left, true, true, 0);
-    let left_style = left.style_context();
-    left_style.add_class("left-timer");
-    left_style.add_provider(
-        &{
-            let provider = gtk::CssProvider::new();
-            provider
-                .load_from_data(
-                    format!(
-                        "* {{ background-color: {}; color: {}; }}",
-                        state.config.left_timer.color, state.config.left_timer.text_color,
-                    )
-                    .as_bytes(),
-                )
-                .unwrap();
-            provider
-        },
-        100,
-    );
-    // left_style.set_property("background-color", &state.config.left_timer.color);
+fn open_default_output(
+    stream_error: &Arc<Mutex<Option<String>>>,
+) -> Result<rodio::OutputStream, String> {
+    return rodio::OutputStreamBuilder::from_default_device()
+        .and_then(|builder| {
+            builder
+                .with_error_callback(on_stream_error(stream_error))
+                .open_stream_or_fallback()
+        })
+        .map_err(|err| err.to_string());
+}
 
-    let left_label = gtk::Label::new(Some("Test left"));
-    if state.config.left_timer.flipped {
-        left_label.set_angle(180.0);
+/// Opens the default output, or the fallback device if the default can't be opened, returning
+/// whether it fell back. Failures of the stream once it's running are recorded in
+/// `stream_error`.
+fn open_output(
+    fallback_device: Option<&str>,
+    stream_error: &Arc<Mutex<Option<String>>>,
+) -> Result<(rodio::OutputStream, bool), String> {
+    let err = match open_default_output(stream_error) {
+        Ok(output_stream) => return Ok((output_stream, false)),
+        Err(err) => err,
+    };
+    let Some(name) = fallback_device else {
+        return Err(err.to_string());
+    };
+    log::warn!("Default audio output unavailable ({err}), trying {name}");
+    let device = rodio::cpal::default_host()
+        .output_devices()
+        .map_err(|err| err.to_string())?
+        .find(|device| device.name().is_ok_and(|device_name| device_name == name))
+        .ok_or_else(|| format!("no audio device named {name}"))?;
+    return rodio::OutputStreamBuilder::from_device(device)
+        .and_then(|builder| {
+            builder
+                .with_error_callback(on_stream_error(stream_error))
+                .open_stream_or_fallback()
+        })
+        .map(|output_stream| (output_stream, true))
+        .map_err(|err| err.to_string());
+}
+
+/// Logs a file that couldn't be played, and keeps it to be reported on screen
+fn report_failure(failure: &Mutex<Option<String>>, file_path: &Path, err: String) {
+    let message = format!("Couldn't play {}: {err}", file_path.display());
+    log::error!("{message}");
+    *failure.lock().unwrap() = Some(message);
+}
+
+/// Loops and pans a track as asked
+fn arrange_track(source: Box<dyn Source + Send>, repeat: bool, pan: f32) -> Box<dyn Source + Send> {
+    let channels = source.channels();
+    let source: Box<dyn Source + Send> = match repeat {
+        true => Box::new(source.repeat_infinite()),
+        false => source,
+    };
+    if pan == 0.0 {
+        return source;
     }
-    left.pack_start(&left_label, true, true, 3);
+    // Panning mixes the track down to mono, summing rather than averaging its channels, so
+    // scale them back down
+    let scale = 1.0 / f32::from(channels);
+    let left = (1.0 - pan).min(1.0) * scale;
+    let right = (1.0 + pan).min(1.0) * scale;
+    return Box::new(rodio::source::ChannelVolume::new(source, vec![left, right]));
+}
 
-    let center = gtk::Box::new(gtk::Orientation::Horizontal, 0);
-    bar.pack_start(&center, false, false, 0);
-    let center_style = center.style_context();
-    center_style.add_class("center-timer");
-    center_style.add_provider(
-        &{
-            let provider = gtk::CssProvider::new();
-            provider
-                .load_from_data(
-                    format!(
-                        "* {{ background-color: {}; color: {}; }}",
-                        state.config.game_timer.color, state.config.game_timer.text_color,
-                    )
-                    .as_bytes(),
-                )
-                .unwrap();
-            provider
-        },
-        100,
-    );
-    let center_label = gtk::Label::new(Some("Test center"));
-    center_label.set_angle(90.0);
-    center.pack_start(&center_label, true, true, 3);
+// How often a fading player's volume is lowered
+const FADE_STEP: Duration = Duration::from_millis(10);
+const HORN_SAMPLE_RATE: u32 = 48_000;
+const BEEP_FREQUENCY_HZ: f32 = 1000.0;
+const CLICK_FREQUENCY_HZ: f32 = 1500.0;
+const CLICK_LENGTH: Duration = Duration::from_millis(30);
+const BEEP_LENGTH: Duration = Duration::from_millis(200);
+// Softens the start of each tone so it doesn't click
+const TONE_FADE: Duration = Duration::from_millis(15);
 
-    let right = gtk::Box::new(gtk::Orientation::Horizontal, 0);
-    bar.pack_end(&right, true, true, 0);
-    let right_style = right.style_context();
-    right_style.add_class("right-timer");
-    right_style.add_provider(
-        &{
-            let provider = gtk::CssProvider::new();
-            provider
-                .load_from_data(
-                    format!(
-                        "* {{ background-color: {}; color: {}; }}",
-                        state.config.right_timer.color, state.config.right_timer.text_color,
-                    )
-                    .as_bytes(),
-                )
-                .unwrap();
-            provider
-        },
-        100,
+fn default_horn_frequency_hz() -> f32 {
+    return 440.0;
+}
+fn default_horn_blast_ms() -> u64 {
+    return 1000;
+}
+fn default_horn_gap_ms() -> u64 {
+    return 500;
+}
+fn default_horn_to_line_blasts() -> u32 {
+    return 2;
+}
+fn default_horn_start_blasts() -> u32 {
+    return 1;
+}
+fn default_horn_end_blasts() -> u32 {
+    return 3;
+}
+fn default_horn_hold_blasts() -> u32 {
+    return 5;
+}
+
+/// Horn blasts synthesized for the standard range signals, so no audio files are needed. Set
+/// a count to 0 to leave that signal out.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct HornConfig {
+    #[serde(default = "default_horn_frequency_hz")]
+    frequency_hz: f32,
+    #[serde(default = "default_horn_blast_ms")]
+    blast_ms: u64,
+    #[serde(default = "default_horn_gap_ms")]
+    gap_ms: u64,
+    /// Calling archers to the line, as the preparation period starts
+    #[serde(default = "default_horn_to_line_blasts")]
+    to_line_blasts: u32,
+    /// Starting shooting
+    #[serde(default = "default_horn_start_blasts")]
+    start_blasts: u32,
+    /// Ending shooting, to go and collect arrows
+    #[serde(default = "default_horn_end_blasts")]
+    end_blasts: u32,
+    /// Stopping shooting straight away for a hold
+    #[serde(default = "default_horn_hold_blasts")]
+    hold_blasts: u32,
+}
+
+/// One click followed by silence lasting the rest of `interval`. Only the click is kept in
+/// memory, however long the interval.
+fn click_track(interval: Duration) -> impl Source<Item = f32> + Send {
+    let rate = HORN_SAMPLE_RATE as f32;
+    let click_length = (CLICK_LENGTH.as_secs_f32() * rate) as usize;
+    let samples: Vec<f32> = (0..click_length)
+        .map(|i| {
+            // A quickly decaying tone, which sounds like a tick
+            let decay = 1.0 - i as f32 / click_length as f32;
+            return (std::f32::consts::TAU * CLICK_FREQUENCY_HZ * i as f32 / rate).sin()
+                * decay
+                * 0.5;
+        })
+        .collect();
+    let click = rodio::buffer::SamplesBuffer::new(1, HORN_SAMPLE_RATE, samples);
+    return rodio::source::Zero::new(1, HORN_SAMPLE_RATE)
+        .take_duration(interval)
+        .mix(click);
+}
+
+/// Clicks while a timer runs, for rhythm training. Set either `bpm` or `interval_secs`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct MetronomeConfig {
+    bpm: Option<f64>,
+    /// Time between clicks, e.g. the time allowed per shot
+    interval_secs: Option<f64>,
+    /// Click from startup, rather than once toggled on
+    #[serde(default)]
+    enabled: bool,
+}
+impl MetronomeConfig {
+    pub fn interval(&self) -> Option<Duration> {
+        let secs = match (self.bpm, self.interval_secs) {
+            (Some(bpm), _) => 60.0 / bpm,
+            (None, Some(interval_secs)) => interval_secs,
+            (None, None) => return None,
+        };
+        return Duration::try_from_secs_f64(secs).ok();
+    }
+}
+
+/// A sound played as any countdown passes a remaining time
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct Milestone {
+    at_secs: u64,
+    /// Beeps when not given
+    sound: Option<PathBuf>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+enum Side {
+    Left,
+    Right,
+}
+impl Side {
+    /// Lowercase name, as used in the event log
+    pub fn name(self) -> &'static str {
+        return match self {
+            Side::Left => "left",
+            Side::Right => "right",
+        };
+    }
+    pub fn other(self) -> Self {
+        return match self {
+            Side::Left => Side::Right,
+            Side::Right => Side::Left,
+        };
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+enum TimerMode {
+    #[default]
+    Stopwatch,
+    Countdown,
+}
+
+/// Where a timer's label sits relative to its clock
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+enum LabelPosition {
+    #[default]
+    Above,
+    Below,
+}
+
+/// How the timers are spread over the attached monitors
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+enum MonitorMode {
+    /// One window, on whichever monitor the window manager puts it
+    #[default]
+    Single,
+    /// The full display on every monitor
+    Mirror,
+    /// The left timer on the first monitor and the right one on the second, each with the game
+    /// timer. Any further monitors mirror the full display.
+    Split,
+}
+
+/// How the two timers are arranged on screen
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+enum Layout {
+    /// Left and right timers side by side with the game timer between them
+    #[default]
+    Horizontal,
+    /// Left timer on top and right one at the bottom, for portrait displays
+    Vertical,
+}
+
+/// Where the logo is drawn
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+enum LogoPosition {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    #[default]
+    BottomRight,
+    /// In the center column, beside the game timer
+    Center,
+}
+
+fn default_wall_clock_format() -> String {
+    return "%H:%M".to_owned();
+}
+fn default_wall_clock_idle_secs() -> u64 {
+    return 30;
+}
+
+/// Time of day shown in place of the timers while nothing's running, e.g. during breaks
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct WallClockConfig {
+    /// strftime-style format, e.g. "%H:%M" or "%-I:%M %p"
+    #[serde(default = "default_wall_clock_format")]
+    format: String,
+    /// How long everything has to be stopped before the clock comes up, so the last times
+    /// stay readable for a while
+    #[serde(default = "default_wall_clock_idle_secs")]
+    idle_secs: u64,
+}
+
+fn default_logo_height() -> i32 {
+    return 120;
+}
+
+/// Club or sponsor image shown on the display
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct LogoConfig {
+    path: PathBuf,
+    #[serde(default)]
+    position: LogoPosition,
+    /// Height it's scaled to in pixels, keeping its proportions
+    #[serde(default = "default_logo_height")]
+    height: i32,
+    /// Space between the logo and the edges of the screen, in pixels
+    #[serde(default)]
+    margin: i32,
+}
+
+/// Which of the timers a window shows
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ScreenPart {
+    All,
+    Left,
+    Right,
+}
+
+fn default_countdown_secs() -> u64 {
+    return 240;
+}
+fn default_volume() -> f32 {
+    return 1.0;
+}
+fn default_loop() -> bool {
+    return true;
+}
+fn default_cues_over_music() -> bool {
+    return true;
+}
+fn default_duck_level() -> f32 {
+    return 0.3;
+}
+fn default_normalize_loudness() -> bool {
+    return true;
+}
+fn default_fade_out_ms() -> u64 {
+    return 500;
+}
+fn default_color_transition_ms() -> u64 {
+    return 500;
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct TimerConfig {
+    color: String,
+    text_color: String,
+    /// A file, a directory of them or a list of either. Each end moves on to the next track.
+    music_file: Option<MusicSource>,
+    /// Play a playlist's tracks in a random order
+    #[serde(default)]
+    shuffle: bool,
+    /// Volume of `music_file` from 0 to 1, relative to the master volume
+    #[serde(default = "default_volume")]
+    volume: f32,
+    /// Loop `music_file` for as long as the timer runs, rather than playing it once
+    #[serde(rename = "loop", default = "default_loop")]
+    loop_music: bool,
+    /// Moves this side's music and sounds towards the left (-1) or right (1) speaker
+    #[serde(default)]
+    pan: f32,
+    #[serde(default)]
+    flipped: bool,
+    #[serde(default)]
+    mode: TimerMode,
+    /// Only used in countdown mode
+    #[serde(default = "default_countdown_secs")]
+    time_secs: u64,
+    /// Played when a countdown reaches zero
+    end_sfx: Option<PathBuf>,
+    /// Keep counting past zero and show how late the archer is
+    #[serde(default)]
+    overtime: bool,
+    /// Count-in run before this side's timer starts
+    prep: Option<PrepConfig>,
+    /// Name of a `time_presets` entry to use instead of `time_secs`
+    preset: Option<String>,
+    /// Lane or target identifier shown at the top of the side, e.g. "Target 12 – A/B"
+    target: Option<String>,
+    /// Caption shown next to the clock, e.g. "LEFT LANE"
+    label: Option<String>,
+    /// Archer or team shooting on this side, unless one's assigned from the roster
+    name: Option<String>,
+    /// Show the time used so far beneath a countdown, e.g. for settling protests
+    #[serde(default)]
+    show_elapsed: bool,
+    flash: Option<FlashConfig>,
+    #[serde(default)]
+    label_position: LabelPosition,
+    #[serde(default)]
+    thresholds: Vec<ColorThreshold>,
+    phase_colors: Option<PhaseColors>,
+}
+
+/// Backgrounds a side switches between as it's run, in place of `color`. Phases left out keep
+/// `color`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct PhaseColors {
+    /// While the side's count-in or the sequence's preparation period runs
+    prep: Option<String>,
+    shooting: Option<String>,
+    /// While the sequence is in its warning period, or a countdown side has less than
+    /// `warning_secs` left
+    warning: Option<String>,
+    #[serde(default = "default_warning_secs")]
+    warning_secs: u64,
+    stopped: Option<String>,
+}
+
+/// What a side is doing, which its panel is colored and styled by
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SidePhase {
+    Prep,
+    Shooting,
+    Warning,
+    Stopped,
+}
+impl SidePhase {
+    const ALL: [SidePhase; 4] = [
+        SidePhase::Prep,
+        SidePhase::Shooting,
+        SidePhase::Warning,
+        SidePhase::Stopped,
+    ];
+    /// Style class the side's panel gets, for custom CSS
+    pub fn class(self) -> &'static str {
+        return match self {
+            SidePhase::Prep => "phase-prep",
+            SidePhase::Shooting => "phase-shooting",
+            SidePhase::Warning => "phase-warning",
+            SidePhase::Stopped => "phase-stopped",
+        };
+    }
+}
+
+/// Named countdown length, e.g. the extended shooting time used in para events
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct TimePreset {
+    name: String,
+    time_secs: u64,
+}
+
+/// Background a countdown switches to once its remaining time drops below `below_secs`
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct ColorThreshold {
+    below_secs: u64,
+    color: String,
+}
+
+fn default_flash_below_secs() -> u64 {
+    return 10;
+}
+fn default_flash_rate_hz() -> f64 {
+    return 2.0;
+}
+
+/// What flashes in a countdown's final seconds
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+enum FlashTarget {
+    #[default]
+    Digits,
+    /// Swaps the panel's background and text colors
+    Background,
+}
+
+/// Flashing that shows a countdown is nearly out, for archers who can't hear the audio
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct FlashConfig {
+    #[serde(default = "default_flash_below_secs")]
+    below_secs: u64,
+    /// Flashes per second
+    #[serde(default = "default_flash_rate_hz")]
+    rate_hz: f64,
+    #[serde(default)]
+    target: FlashTarget,
+}
+
+/// Whether a flashing countdown is in the off half of its flash. Timed off the remaining
+/// time, so every window flashes together.
+fn flash_off(flash: &FlashConfig, remaining: Duration) -> Option<FlashTarget> {
+    let half_periods = (remaining.as_secs_f64() * flash.rate_hz * 2.0) as u64;
+    return (half_periods % 2 == 1).then_some(flash.target);
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct PrepConfig {
+    #[serde(default = "default_prep_secs")]
+    time_secs: u64,
+    #[serde(default = "default_prep_color")]
+    color: String,
+    sfx: Option<PathBuf>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct GameTimerConfig {
+    time_secs: u64,
+    color: String,
+    text_color: String,
+    start_sfx: Option<PathBuf>,
+    end_sfx: Option<PathBuf>,
+    /// Time left when the traffic light turns amber, when there's no shooting sequence
+    #[serde(default = "default_warning_secs")]
+    warning_secs: u64,
+    /// Flashing in the countdown's final seconds, including each phase of a sequence
+    flash: Option<FlashConfig>,
+}
+
+fn default_prep_secs() -> u64 {
+    return 10;
+}
+fn default_shooting_secs() -> u64 {
+    return 240;
+}
+fn default_warning_secs() -> u64 {
+    return 30;
+}
+fn default_prep_color() -> String {
+    return String::from("red");
+}
+fn default_shooting_color() -> String {
+    return String::from("green");
+}
+fn default_warning_color() -> String {
+    return String::from("yellow");
+}
+
+/// World Archery style shooting sequence driven by the game timer trigger
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct SequenceConfig {
+    #[serde(default = "default_prep_secs")]
+    prep_secs: u64,
+    #[serde(default = "default_shooting_secs")]
+    shooting_secs: u64,
+    /// Remaining time at which the shooting phase switches to the warning color
+    #[serde(default = "default_warning_secs")]
+    warning_secs: u64,
+    #[serde(default = "default_prep_color")]
+    prep_color: String,
+    #[serde(default = "default_shooting_color")]
+    shooting_color: String,
+    #[serde(default = "default_warning_color")]
+    warning_color: String,
+    #[serde(default = "default_prep_color")]
+    end_color: String,
+    prep_sfx: Option<PathBuf>,
+    shooting_sfx: Option<PathBuf>,
+    end_sfx: Option<PathBuf>,
+    /// Shooting lines (e.g. `[AB, CD]`) run back to back within each end. The
+    /// starting line rotates with every start press.
+    #[serde(default)]
+    lines: Vec<String>,
+}
+impl Default for SequenceConfig {
+    fn default() -> Self {
+        return Self {
+            prep_secs: default_prep_secs(),
+            shooting_secs: default_shooting_secs(),
+            warning_secs: default_warning_secs(),
+            prep_color: default_prep_color(),
+            shooting_color: default_shooting_color(),
+            warning_color: default_warning_color(),
+            end_color: default_prep_color(),
+            prep_sfx: None,
+            shooting_sfx: None,
+            end_sfx: None,
+            lines: Vec::new(),
+        };
+    }
+}
+
+fn default_arrows_per_end() -> u32 {
+    return 6;
+}
+
+/// A named round that drives the shooting sequence end by end. Colors, cues and lines come
+/// from the `sequence` section.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct RoundTemplate {
+    name: String,
+    ends: u32,
+    #[serde(default = "default_arrows_per_end")]
+    arrows_per_end: u32,
+    /// Defaults to the `sequence` preparation time
+    prep_secs: Option<u64>,
+    /// Defaults to 40 seconds per arrow
+    shooting_secs: Option<u64>,
+    /// Pause between ends before the next one starts on its own. Without it, each end
+    /// waits for the start trigger.
+    break_secs: Option<u64>,
+}
+
+fn default_shot_secs() -> u64 {
+    return 20;
+}
+
+/// Alternating shooting, where each side's button hands a fresh shot clock to the other side
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct AlternatingConfig {
+    #[serde(default = "default_shot_secs")]
+    shot_secs: u64,
+    /// Played whenever the shot clock changes hands
+    arrow_sfx: Option<PathBuf>,
+    /// Played when a shot clock runs out
+    timeout_sfx: Option<PathBuf>,
+}
+
+fn default_interval_shoot_secs() -> u64 {
+    return 90;
+}
+fn default_interval_rest_secs() -> u64 {
+    return 60;
+}
+fn default_interval_repeats() -> u32 {
+    return 5;
+}
+
+/// Practice mode cycling shoot and rest intervals, started by the game timer trigger
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct IntervalConfig {
+    #[serde(default = "default_interval_shoot_secs")]
+    shoot_secs: u64,
+    #[serde(default = "default_interval_rest_secs")]
+    rest_secs: u64,
+    /// Number of shooting intervals
+    #[serde(default = "default_interval_repeats")]
+    repeats: u32,
+    #[serde(default = "default_shooting_color")]
+    shoot_color: String,
+    #[serde(default = "default_prep_color")]
+    rest_color: String,
+    shoot_music: Option<PathBuf>,
+    rest_music: Option<PathBuf>,
+}
+
+/// Random wait between pressing start and the game timer actually starting
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct RandomDelayConfig {
+    min_ms: u64,
+    max_ms: u64,
+}
+
+/// Score entry for club matches, shown alongside each side's timer
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct ScoringConfig {
+    #[serde(default = "default_arrows_per_end")]
+    arrows_per_end: u32,
+    /// Score by World Archery set points, first to 6, instead of running totals
+    #[serde(default)]
+    sets: bool,
+}
+
+/// Archers assigned to target positions, and where their results are written
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct ParticipantsConfig {
+    /// Tab-separated participant list, exported from Ianseo or with a header row naming its
+    /// columns
+    file: Option<PathBuf>,
+    /// Target number and letter shot from each side, e.g. `12A`
+    left_target: String,
+    right_target: String,
+    /// Directory results are written to whenever a session is finished
+    export_dir: Option<PathBuf>,
+}
+
+fn default_gpio_chip() -> PathBuf {
+    return PathBuf::from("/dev/gpiochip0");
+}
+fn default_left_line() -> InputLine {
+    return InputLine::Line(LineId::Offset(23));
+}
+fn default_right_line() -> InputLine {
+    return InputLine::Line(LineId::Offset(24));
+}
+fn default_game_line() -> InputLine {
+    return InputLine::Line(LineId::Offset(17));
+}
+fn default_shutdown_command() -> Vec<String> {
+    return vec!["systemctl".to_owned(), "poweroff".to_owned()];
+}
+fn default_shutdown_hold_ms() -> u64 {
+    return 3000;
+}
+fn default_active_low() -> bool {
+    return true;
+}
+
+/// Pull resistor applied to an input line
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+enum Bias {
+    /// Leave the line as the board or device tree set it up
+    #[default]
+    AsIs,
+    PullUp,
+    PullDown,
+    Disabled,
+}
+
+/// A GPIO line, by offset or by the name the kernel gives it, such as `GPIO23` or a name set
+/// by a device tree overlay
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(untagged)]
+enum LineId {
+    Offset(u32),
+    Name(String),
+}
+impl LineId {
+    /// The line's offset. Names must have been looked up with `GpioConfig::resolve_names`.
+    pub fn offset(&self) -> u32 {
+        return match *self {
+            LineId::Offset(offset) => offset,
+            LineId::Name(ref name) => panic!("GPIO line {name:?} was never looked up"),
+        };
+    }
+    fn resolve(&mut self, names: &HashMap<String, u32>) -> Result<(), String> {
+        if let LineId::Name(ref name) = *self {
+            let offset = *names
+                .get(name)
+                .ok_or_else(|| format!("the GPIO chip has no line named {name:?}"))?;
+            *self = LineId::Offset(offset);
+        }
+        return Ok(());
+    }
+}
+impl fmt::Display for LineId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return match *self {
+            LineId::Offset(offset) => write!(f, "{offset}"),
+            LineId::Name(ref name) => write!(f, "{name:?}"),
+        };
+    }
+}
+
+/// A button's input line, given on its own for an active-low button or with its wiring
+/// spelled out
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(untagged)]
+enum InputLine {
+    Line(LineId),
+    Wired {
+        line: LineId,
+        #[serde(default = "default_active_low")]
+        active_low: bool,
+        #[serde(default)]
+        bias: Bias,
+        /// Contact chatter shorter than this is ignored, by the kernel through libgpiod and by
+        /// the app otherwise
+        debounce_ms: Option<u64>,
+    },
+}
+impl InputLine {
+    pub fn line(&self) -> &LineId {
+        return match *self {
+            InputLine::Line(ref line) => line,
+            InputLine::Wired { ref line, .. } => line,
+        };
+    }
+    fn line_mut(&mut self) -> &mut LineId {
+        return match *self {
+            InputLine::Line(ref mut line) => line,
+            InputLine::Wired { ref mut line, .. } => line,
+        };
+    }
+    pub fn offset(&self) -> u32 {
+        return self.line().offset();
+    }
+    pub fn debounce(&self) -> Option<Duration> {
+        return match *self {
+            InputLine::Line(_) => None,
+            InputLine::Wired { debounce_ms, .. } => debounce_ms.map(Duration::from_millis),
+        };
+    }
+    pub fn active_low(&self) -> bool {
+        return match *self {
+            InputLine::Line(_) => true,
+            InputLine::Wired { active_low, .. } => active_low,
+        };
+    }
+    pub fn bias(&self) -> Bias {
+        return match *self {
+            InputLine::Line(_) => Bias::AsIs,
+            InputLine::Wired { bias, .. } => bias,
+        };
+    }
+}
+
+/// Output lines driven high while the matching timer runs, e.g. for LED panels or lamp stacks
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+struct IndicatorConfig {
+    game_line: Option<LineId>,
+    left_line: Option<LineId>,
+    right_line: Option<LineId>,
+}
+
+fn default_encoder_step_secs() -> u64 {
+    return 10;
+}
+
+/// Rotary encoder that dials the shooting time up and down between ends
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct EncoderConfig {
+    a_line: InputLine,
+    b_line: InputLine,
+    /// Time added or taken away per click
+    #[serde(default = "default_encoder_step_secs")]
+    step_secs: u64,
+}
+
+/// Output lines of a red/amber/green light stack following the shooting phases
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct TrafficLightConfig {
+    red_line: LineId,
+    amber_line: LineId,
+    green_line: LineId,
+}
+
+fn default_start_blasts() -> Vec<u64> {
+    return vec![500, 500, 500];
+}
+fn default_stop_blasts() -> Vec<u64> {
+    return vec![500];
+}
+fn default_reset_blasts() -> Vec<u64> {
+    return vec![500, 500, 500, 500, 500];
+}
+
+/// A timer starting, stopping or being cleared. Ordered by which cue wins when several
+/// happen at once.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum TimerEvent {
+    Stop,
+    Clear,
+    Start,
+}
+impl TimerEvent {
+    pub fn name(self) -> &'static str {
+        return match self {
+            TimerEvent::Stop => "stop",
+            TimerEvent::Clear => "clear",
+            TimerEvent::Start => "start",
+        };
+    }
+}
+
+/// Which of the buzzer's patterns to sound
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BuzzerSignal {
+    Start,
+    Stop,
+    Reset,
+}
+
+/// Buzzer or horn sounded when the game timer starts, stops and is reset. Patterns are
+/// alternating on and off times in milliseconds.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct BuzzerConfig {
+    line: LineId,
+    #[serde(default = "default_start_blasts")]
+    start: Vec<u64>,
+    #[serde(default = "default_stop_blasts")]
+    stop: Vec<u64>,
+    #[serde(default = "default_reset_blasts")]
+    reset: Vec<u64>,
+}
+
+/// Where the buttons are wired, so the same build works across boards and HATs
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct GpioConfig {
+    /// Path to the chip, or its label or name, such as `pinctrl-bcm2711` or `gpiochip0`
+    #[serde(default = "default_gpio_chip")]
+    chip: PathBuf,
+    #[serde(default = "default_left_line")]
+    left_line: InputLine,
+    #[serde(default = "default_right_line")]
+    right_line: InputLine,
+    /// Starts the game timer, or freezes everything if it's running
+    #[serde(default = "default_game_line")]
+    game_line: InputLine,
+    /// Optional judge hold button
+    hold_line: Option<InputLine>,
+    /// Optional button that resets the timers straight away
+    reset_line: Option<InputLine>,
+    /// Optional power button or UPS low-battery signal that saves everything and shuts the
+    /// system down cleanly
+    shutdown_line: Option<InputLine>,
+    /// Run once the shutdown line has been held for `shutdown_hold_ms`
+    #[serde(default = "default_shutdown_command")]
+    shutdown_command: Vec<String>,
+    /// How long the shutdown line must stay asserted before shutting down, so a knock or a
+    /// glitch on the line can't power the system off
+    #[serde(default = "default_shutdown_hold_ms")]
+    shutdown_hold_ms: u64,
+    /// LED blinking a heartbeat while all is well, fast for a GPIO fault and long blinks for
+    /// an audio fault
+    status_line: Option<LineId>,
+    encoder: Option<EncoderConfig>,
+    #[serde(default)]
+    indicators: IndicatorConfig,
+    buzzer: Option<BuzzerConfig>,
+    traffic_light: Option<TrafficLightConfig>,
+    led_strip: Option<LedStripConfig>,
+    seven_segment: Option<SevenSegmentConfig>,
+    /// Keyboards and USB button boxes whose keys act as buttons alongside the GPIO ones
+    #[serde(default)]
+    input_devices: Vec<InputDeviceConfig>,
+    /// Receiver for wireless button fobs, e.g. 433 MHz or LoRa, on a serial port
+    serial: Option<SerialConfig>,
+    /// I2C expander the buttons and indicators are wired to, in place of the chip
+    expander: Option<ExpanderConfig>,
+    /// Simulates the buttons with events read from this UNIX socket, or stdin for `-`,
+    /// instead of using any GPIO hardware
+    simulator: Option<PathBuf>,
+}
+impl Default for GpioConfig {
+    fn default() -> Self {
+        return Self {
+            chip: default_gpio_chip(),
+            left_line: default_left_line(),
+            right_line: default_right_line(),
+            game_line: default_game_line(),
+            hold_line: None,
+            reset_line: None,
+            shutdown_line: None,
+            shutdown_command: default_shutdown_command(),
+            shutdown_hold_ms: default_shutdown_hold_ms(),
+            status_line: None,
+            encoder: None,
+            indicators: IndicatorConfig::default(),
+            buzzer: None,
+            traffic_light: None,
+            led_strip: None,
+            seven_segment: None,
+            input_devices: Vec::new(),
+            serial: None,
+            expander: None,
+            simulator: None,
+        };
+    }
+}
+impl GpioConfig {
+    /// Swaps any line names for their offsets on `chip`
+    pub fn resolve_names(&mut self, chip: &Chip) -> Result<(), String> {
+        let names = chip.line_names()?;
+        let mut lines = vec![
+            self.left_line.line_mut(),
+            self.right_line.line_mut(),
+            self.game_line.line_mut(),
+        ];
+        lines.extend(self.hold_line.as_mut().map(InputLine::line_mut));
+        lines.extend(self.reset_line.as_mut().map(InputLine::line_mut));
+        lines.extend(self.shutdown_line.as_mut().map(InputLine::line_mut));
+        if let Some(ref mut encoder) = self.encoder {
+            lines.push(encoder.a_line.line_mut());
+            lines.push(encoder.b_line.line_mut());
+        }
+        lines.extend(self.indicators.game_line.as_mut());
+        lines.extend(self.indicators.left_line.as_mut());
+        lines.extend(self.indicators.right_line.as_mut());
+        if let Some(ref mut buzzer) = self.buzzer {
+            lines.push(&mut buzzer.line);
+        }
+        lines.extend(self.status_line.as_mut());
+        if let Some(ref mut traffic_light) = self.traffic_light {
+            lines.push(&mut traffic_light.red_line);
+            lines.push(&mut traffic_light.amber_line);
+            lines.push(&mut traffic_light.green_line);
+        }
+        for line in lines {
+            line.resolve(&names)?;
+        }
+        return Ok(());
+    }
+}
+
+fn default_checkpoint_interval_secs() -> u64 {
+    return 5;
+}
+
+fn default_refresh_interval_ms() -> u64 {
+    return 100;
+}
+
+fn default_button_debounce_ms() -> u64 {
+    return 25;
+}
+
+fn default_reset_hold_ms() -> Option<u64> {
+    return Some(3000);
+}
+
+fn default_extra_time_secs() -> u64 {
+    return 30;
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct Config {
+    button_toggle: bool,
+    /// Let both sides run at once, with each start action toggling its own side
+    #[serde(default)]
+    simultaneous: bool,
+    game_timer: GameTimerConfig,
+    left_timer: TimerConfig,
+    right_timer: TimerConfig,
+    sequence: Option<SequenceConfig>,
+    #[serde(default)]
+    rounds: Vec<RoundTemplate>,
+    alternating: Option<AlternatingConfig>,
+    intervals: Option<IntervalConfig>,
+    random_delay: Option<RandomDelayConfig>,
+    scoring: Option<ScoringConfig>,
+    participants: Option<ParticipantsConfig>,
+    /// YAML list of archers that can be assigned to each side at runtime
+    roster: Option<PathBuf>,
+    /// Countdown lengths each side can be switched between at runtime
+    #[serde(default)]
+    time_presets: Vec<TimePreset>,
+    /// Show average, fastest and slowest end times along the bottom of the screen
+    #[serde(default)]
+    show_stats: bool,
+    #[serde(default)]
+    font: FontConfig,
+    /// How windows are opened when more than one monitor is attached
+    #[serde(default)]
+    monitors: MonitorMode,
+    #[serde(default)]
+    layout: Layout,
+    /// Show the pointer again when the mouse moves, until it's been still this long
+    cursor_reveal_secs: Option<u64>,
+    /// Outline the side that's shooting and dim the other, while only one is
+    #[serde(default)]
+    highlight_active: bool,
+    /// Show the end, line and sequence phase in the center column, in place of the caption
+    /// beside the game timer
+    #[serde(default)]
+    info_panel: bool,
+    /// Run in a window instead of fullscreen, as with `--windowed`
+    #[serde(default)]
+    windowed: bool,
+    /// Degrees the whole display is turned clockwise, for screens mounted on their side
+    #[serde(default)]
+    rotation: u16,
+    /// Master volume from 0 to 1
+    #[serde(default = "default_volume")]
+    volume: f32,
+    /// Played once whenever a timer starts
+    start_sound: Option<PathBuf>,
+    /// Played once whenever a timer stops
+    stop_sound: Option<PathBuf>,
+    /// Played once whenever the timers are reset
+    reset_sound: Option<PathBuf>,
+    /// Play the sounds above over any music, rather than stopping it
+    #[serde(default = "default_cues_over_music")]
+    cues_over_music: bool,
+    /// Output device (as `aplay -L` names it) used when the default one can't be opened, e.g.
+    /// the Pi's own headphone jack when a USB DAC is unplugged
+    audio_fallback_device: Option<String>,
+    /// Music volume, relative to its usual volume, while cues and announcements play over it
+    #[serde(default = "default_duck_level")]
+    duck_level: f32,
+    /// Play music tracks at the same loudness, measuring them in the background at startup
+    #[serde(default = "default_normalize_loudness")]
+    normalize_loudness: bool,
+    /// How long music fades out for when a timer stops or is cleared; 0 cuts it off
+    #[serde(default = "default_fade_out_ms")]
+    fade_out_ms: u64,
+    /// Palette shown in place of the configured colors, e.g. for color blind archers
+    theme: Option<Theme>,
+    /// Stylesheet loaded over the built-in one at startup
+    css: Option<PathBuf>,
+    logo: Option<LogoConfig>,
+    wall_clock: Option<WallClockConfig>,
+    ticker: Option<TickerConfig>,
+    /// How long panels take to fade between background colors; 0 switches them instantly
+    #[serde(default = "default_color_transition_ms")]
+    color_transition_ms: u64,
+    horn: Option<HornConfig>,
+    /// Directory of clips said as countdowns pass the time each is named for, e.g. `30s.ogg`,
+    /// `10s.ogg`, `5.ogg` to `1.ogg` and `stop.ogg`
+    voice_pack: Option<PathBuf>,
+    #[serde(default)]
+    milestones: Vec<Milestone>,
+    metronome: Option<MetronomeConfig>,
+    /// Spoken announcements (requires a text-to-speech engine such as espeak-ng)
+    speech: Option<SpeechConfig>,
+    /// Move on to the next end whenever the timers are reset
+    #[serde(default)]
+    reset_advances_end: bool,
+    /// Time granted by a judge after an equipment failure
+    #[serde(default = "default_extra_time_secs")]
+    extra_time_secs: u64,
+    /// Countdowns below this many seconds are shown with tenths
+    tenths_below_secs: Option<u64>,
+    /// Template times are shown with, e.g. `{m:02}:{s:02}` or `{h}:{m:02}:{s:02}`
+    #[serde(default)]
+    time_format: TimeFormat,
+    /// How often the display is redrawn
+    #[serde(default = "default_refresh_interval_ms")]
+    refresh_interval_ms: u64,
+    #[serde(default)]
+    gpio: GpioConfig,
+    /// Older spelling of `gpio.hold_line`
+    hold_gpio_line: Option<u32>,
+    /// How long both side buttons must be held to reset the timers, or `None` to disable it
+    #[serde(default = "default_reset_hold_ms")]
+    reset_hold_ms: Option<u64>,
+    /// How long the GPIO buttons must settle before a press is acted on
+    #[serde(default = "default_button_debounce_ms")]
+    button_debounce_ms: u64,
+    /// Wall-clock time (`HH:MM` or `HH:MM:SS`) at which to start the game timer
+    scheduled_start: Option<String>,
+    /// CSV file that every timer start, stop and clear is appended to
+    event_log: Option<PathBuf>,
+    /// SQLite database that sessions and ends are stored in (requires the `sqlite` feature)
+    database: Option<PathBuf>,
+    /// File the timer state is periodically saved to and restored from after a crash
+    checkpoint: Option<PathBuf>,
+    #[serde(default = "default_checkpoint_interval_secs")]
+    checkpoint_interval_secs: u64,
+    /// Keys for each action, replacing that action's default keys
+    #[serde(default)]
+    keybindings: HashMap<Action, KeyNames>,
+}
+impl Config {
+    pub fn hold_line(&self) -> Option<InputLine> {
+        return self.gpio.hold_line.clone().or(self
+            .hold_gpio_line
+            .map(|line| InputLine::Line(LineId::Offset(line))));
+    }
+    /// Every music track that can be played
+    pub fn music_tracks(&self) -> Vec<PathBuf> {
+        let mut tracks = Vec::new();
+        for timer in [&self.left_timer, &self.right_timer] {
+            if let Some(ref music) = timer.music_file {
+                tracks.extend(music.tracks());
+            }
+        }
+        if let Some(ref intervals) = self.intervals {
+            tracks.extend(intervals.shoot_music.iter().cloned());
+            tracks.extend(intervals.rest_music.iter().cloned());
+        }
+        return tracks;
+    }
+}
+
+/// Which timers were running when a hold was called, so they can be resumed
+#[derive(Debug, Clone, Copy)]
+struct HeldTimers {
+    game: bool,
+    left: bool,
+    right: bool,
+    left_prep: bool,
+    right_prep: bool,
+    shot_clock: bool,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct IntervalState {
+    /// 1-based shooting interval
+    round: u32,
+    resting: bool,
+}
+
+struct ShotClock {
+    side: Side,
+    timer: Timer,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+enum SequencePhase {
+    Idle,
+    Preparation,
+    Shooting,
+    Warning,
+    EndOfEnd,
+    /// Between ends of a round template
+    Break,
+}
+impl SequencePhase {
+    /// Name shown on the info panel
+    pub fn name(self) -> Option<&'static str> {
+        return match self {
+            SequencePhase::Idle => None,
+            SequencePhase::Preparation => Some("Preparation"),
+            SequencePhase::Shooting => Some("Shooting"),
+            SequencePhase::Warning => Some("Warning"),
+            SequencePhase::EndOfEnd => Some("End over"),
+            SequencePhase::Break => Some("Break"),
+        };
+    }
+}
+
+fn load_voice_pack(directory: &Path) -> Option<VoicePack> {
+    return match VoicePack::load(directory) {
+        Ok(voice_pack) => Some(voice_pack),
+        Err(err) => {
+            log::error!("Failed to load voice pack {directory:?}: {err}");
+            None
+        }
+    };
+}
+
+struct ApplicationState {
+    config: Config,
+    // File and profile the config was loaded from. Reports are saved beside the file.
+    source: ConfigSource,
+    game_timer: Timer,
+    left_timer: SideTimer,
+    right_timer: SideTimer,
+    audio_controller: AudioController,
+    // Active shooting sequence, with any selected round's timings applied
+    sequence: Option<SequenceConfig>,
+    // Index into `rounds` of the selected round template
+    round: Option<usize>,
+    phase: SequencePhase,
+    // Current end, also used to rotate which line shoots first
+    end_number: usize,
+    // Whether the current end has been shot, so the next start moves on to a new end
+    end_started: bool,
+    // Which line of the current end is shooting
+    line_index: usize,
+    held: Option<HeldTimers>,
+    // Why the GPIO buttons can't be used right now, if they can't
+    gpio_error: Option<String>,
+    // Cue for the timer events since the last tick
+    cue: Option<TimerEvent>,
+    // Where to send buzzer signals while the buzzer is open
+    buzzer_signals: Option<futures::channel::mpsc::UnboundedSender<BuzzerSignal>>,
+    // When the shooting time was last dialed in, and to what
+    time_adjusted: Option<(Instant, Duration)>,
+    // Count-ins running ahead of each side's timer
+    left_prep: Option<Timer>,
+    right_prep: Option<Timer>,
+    left_playlist: Playlist,
+    right_playlist: Playlist,
+    scheduled_start: Option<chrono::NaiveDateTime>,
+    left_splits: Vec<Duration>,
+    right_splits: Vec<Duration>,
+    shot_clock: Option<ShotClock>,
+    interval: Option<IntervalState>,
+    // When a randomly delayed start will actually begin
+    pending_start: Option<Instant>,
+    left_arrows: u32,
+    right_arrows: u32,
+    session: Session,
+    show_stats: bool,
+    // Index into `time_presets` for each side
+    left_preset: Option<usize>,
+    right_preset: Option<usize>,
+    // Whether the zero cue has already sounded for a countdown running in overtime
+    left_alerted: bool,
+    right_alerted: bool,
+    event_log: Option<EventLog>,
+    #[cfg(feature = "sqlite")]
+    store: Option<storage::SessionStore>,
+    last_checkpoint: Option<Instant>,
+    // Set once the shutdown command has run, so a held shutdown line doesn't run it again
+    shutting_down: bool,
+    // Game timer's remaining time when announcements were last checked, while it's counting
+    announced_remaining: Option<Duration>,
+    // Passing problem shown to the operator, and when it happened
+    toast: Option<(String, Instant)>,
+    // When every timer was last seen stopped, for bringing up the wall clock
+    idle_since: Option<Instant>,
+    // When both side buttons were pressed together, while they're held towards a reset
+    reset_hold_started: Option<Instant>,
+    voice_pack: Option<VoicePack>,
+    // Rebuilt whenever the configured bindings change
+    keymap: Keymap,
+    game_watch: CountdownWatch,
+    // Toggled by a key, and only heard while a timer runs
+    metronome_on: bool,
+    left_watch: CountdownWatch,
+    right_watch: CountdownWatch,
+    // Sessions closed out since startup, the last of which the summary screen shows
+    history: Vec<FinishedSession>,
+    show_summary: bool,
+    scoreboard: Option<Scoreboard>,
+    // Archers on each side's target according to the participant list
+    left_archer: Option<Participant>,
+    right_archer: Option<Participant>,
+    roster: Vec<Archer>,
+    // Index into `roster` of the archer assigned to each side, overriding the participant list
+    left_assignment: Option<usize>,
+    right_assignment: Option<usize>,
+}
+impl ApplicationState {
+    pub fn new(config: Config, source: ConfigSource) -> Self {
+        let scheduled_start = match config.scheduled_start {
+            Some(ref time) => match parse_wall_time(time) {
+                Some(time) => Some(next_occurrence(time)),
+                None => {
+                    log::warn!("Ignoring invalid scheduled_start {time:?}");
+                    None
+                }
+            },
+            None => None,
+        };
+        let find_preset = |timer_config: &TimerConfig| {
+            let name = timer_config.preset.as_ref()?;
+            let index = config
+                .time_presets
+                .iter()
+                .position(|preset| &preset.name == name);
+            if index.is_none() {
+                log::warn!("Ignoring unknown time preset {name:?}");
+            }
+            return index;
+        };
+        let left_preset = find_preset(&config.left_timer);
+        let right_preset = find_preset(&config.right_timer);
+        let event_log = match config.event_log {
+            Some(ref path) => match EventLog::open(path) {
+                Ok(event_log) => Some(event_log),
+                Err(err) => {
+                    log::error!("Failed to open event log {path:?}: {err}");
+                    None
+                }
+            },
+            None => None,
+        };
+        #[cfg(feature = "sqlite")]
+        let store = match config.database {
+            Some(ref path) => match storage::SessionStore::open(path) {
+                Ok(store) => Some(store),
+                Err(err) => {
+                    log::error!("Failed to open database {path:?}: {err}");
+                    None
+                }
+            },
+            None => None,
+        };
+        #[cfg(not(feature = "sqlite"))]
+        if config.database.is_some() {
+            log::warn!("Ignoring database, as sqlite support was not compiled in");
+        }
+        let participants = match config.participants {
+            Some(ParticipantsConfig {
+                file: Some(ref path),
+                ..
+            }) => match participants::load_participants(path) {
+                Ok(participants) => participants,
+                Err(err) => {
+                    log::error!("Failed to import participants from {path:?}: {err}");
+                    Vec::new()
+                }
+            },
+            _ => Vec::new(),
+        };
+        let (left_archer, right_archer) = match config.participants {
+            Some(ref targets) => (
+                participants::find_participant(&participants, &targets.left_target),
+                participants::find_participant(&participants, &targets.right_target),
+            ),
+            None => (None, None),
+        };
+        for archer in left_archer.iter().chain(&right_archer) {
+            log::info!(
+                "Target {}: {} ({})",
+                archer.target,
+                archer.name,
+                archer.country
+            );
+        }
+        let roster = match config.roster {
+            Some(ref path) => match roster::load_roster(path) {
+                Ok(roster) => roster,
+                Err(err) => {
+                    log::error!("Failed to load roster {path:?}: {err}");
+                    Vec::new()
+                }
+            },
+            None => Vec::new(),
+        };
+        let mut state = Self {
+            game_timer: Timer::new(Duration::from_secs(config.game_timer.time_secs)),
+            left_timer: SideTimer::new(&config.left_timer),
+            right_timer: SideTimer::new(&config.right_timer),
+            audio_controller: AudioController::new(
+                config.volume,
+                Duration::from_millis(config.fade_out_ms),
+                config.duck_level,
+                config.audio_fallback_device.clone(),
+            ),
+            sequence: config.sequence.clone(),
+            round: None,
+            phase: SequencePhase::Idle,
+            end_number: 1,
+            end_started: false,
+            line_index: 0,
+            held: None,
+            gpio_error: None,
+            cue: None,
+            buzzer_signals: None,
+            shutting_down: false,
+            announced_remaining: None,
+            toast: None,
+            idle_since: None,
+            reset_hold_started: None,
+            voice_pack: config.voice_pack.as_deref().and_then(load_voice_pack),
+            keymap: Keymap::new(&config.keybindings),
+            game_watch: CountdownWatch::default(),
+            metronome_on: config
+                .metronome
+                .as_ref()
+                .is_some_and(|metronome| metronome.enabled),
+            left_watch: CountdownWatch::default(),
+            right_watch: CountdownWatch::default(),
+            time_adjusted: None,
+            left_prep: None,
+            right_prep: None,
+            left_playlist: Playlist::default(),
+            right_playlist: Playlist::default(),
+            scheduled_start,
+            left_splits: Vec::new(),
+            right_splits: Vec::new(),
+            shot_clock: None,
+            interval: None,
+            pending_start: None,
+            left_arrows: 0,
+            right_arrows: 0,
+            left_preset,
+            right_preset,
+            session: Session::new(),
+            show_stats: config.show_stats,
+            left_alerted: false,
+            right_alerted: false,
+            event_log,
+            #[cfg(feature = "sqlite")]
+            store,
+            last_checkpoint: None,
+            history: Vec::new(),
+            show_summary: false,
+            left_archer,
+            right_archer,
+            roster,
+            left_assignment: None,
+            right_assignment: None,
+            scoreboard: config
+                .scoring
+                .as_ref()
+                .map(|scoring| Scoreboard::new(scoring.arrows_per_end as usize, scoring.sets)),
+            config,
+            source,
+        };
+        state.apply_preset(Side::Left);
+        state.apply_preset(Side::Right);
+        state.normalize_music();
+        return state;
+    }
+
+    pub fn clear_timers(&mut self) {
+        self.record_end();
+        for (timer, elapsed) in [
+            ("game", self.game_timer.get_elapsed()),
+            ("left", self.left_timer.get_elapsed()),
+            ("right", self.right_timer.get_elapsed()),
+        ] {
+            if !elapsed.is_zero() {
+                self.record(timer, TimerEvent::Clear, elapsed);
+            }
+        }
+        if self.phase != SequencePhase::Idle {
+            self.log_phase(SequencePhase::Idle);
+        }
+        self.left_timer.clear();
+        self.right_timer.clear();
+        self.left_prep = None;
+        self.right_prep = None;
+        self.left_splits.clear();
+        self.right_splits.clear();
+        self.shot_clock = None;
+        self.interval = None;
+        self.pending_start = None;
+        self.left_arrows = 0;
+        self.right_arrows = 0;
+        self.game_timer.clear();
+        self.audio_controller.stop();
+        self.phase = SequencePhase::Idle;
+        self.held = None;
+        self.scheduled_start = None;
+    }
+    pub fn start_game_timer(&mut self) {
+        if self.is_held() {
+            return;
+        }
+        // A second press while waiting on a random delay cancels the start
+        if self.pending_start.take().is_some() {
+            return;
+        }
+        if let Some(ref random_delay) = self.config.random_delay {
+            let min_ms = random_delay.min_ms.min(random_delay.max_ms);
+            let max_ms = random_delay.min_ms.max(random_delay.max_ms);
+            let delay = Duration::from_millis(rand::thread_rng().gen_range(min_ms..=max_ms));
+            self.clear_timers();
+            self.pending_start = Some(Instant::now() + delay);
+            return;
+        }
+        self.begin_game_timer();
+    }
+    fn begin_game_timer(&mut self) {
+        self.clear_timers();
+        self.record("game", TimerEvent::Start, Duration::ZERO);
+        if self.sequence.is_some() {
+            if self.is_round_complete() {
+                self.end_number = 1;
+                self.end_started = false;
+            }
+            self.start_next_end();
+            return;
+        }
+        if self.config.intervals.is_some() {
+            self.enter_interval(IntervalState {
+                round: 1,
+                resting: false,
+            });
+            return;
+        }
+        if let Some(ref start_sfx) = self.config.game_timer.start_sfx {
+            self.audio_controller.play_file(start_sfx);
+            std::thread::sleep(Duration::from_millis(500));
+        }
+        self.sound_horn(|horn| horn.start_blasts);
+        self.signal_buzzer(BuzzerSignal::Start);
+        self.game_timer.start();
+    }
+    pub fn start_left_timer(&mut self) {
+        if self.is_held() {
+            return;
+        }
+        if self.config.alternating.is_some() {
+            self.hand_shot_clock(Side::Left);
+            return;
+        }
+        let toggle = self.config.button_toggle || self.config.simultaneous;
+        if self.left_prep.is_some() {
+            if toggle {
+                self.left_prep = None;
+                self.audio_controller.stop();
+            }
+            return;
+        }
+        if self.left_timer.is_running() && toggle {
+            self.stop_side(Side::Left);
+            return;
+        }
+        if !self.config.simultaneous {
+            self.stop_side(Side::Right);
+            self.right_prep = None;
+        }
+        if let Some(ref prep) = self.config.left_timer.prep {
+            if self.left_timer.is_fresh() {
+                let mut prep_timer = Timer::new(Duration::from_secs(prep.time_secs));
+                prep_timer.start();
+                self.left_prep = Some(prep_timer);
+                let pan = self.config.left_timer.pan;
+                match prep.sfx {
+                    Some(ref prep_sfx) => {
+                        self.audio_controller.play_track(prep_sfx, 1.0, false, pan)
+                    }
+                    None => self.audio_controller.stop(),
+                }
+                return;
+            }
+        }
+        self.begin_left_timer();
+    }
+    fn begin_left_timer(&mut self) {
+        let new_end = self.left_timer.is_fresh();
+        self.left_timer.start();
+        self.record("left", TimerEvent::Start, self.left_timer.get_elapsed());
+        self.play_side_music(Side::Left, new_end);
+    }
+    pub fn start_right_timer(&mut self) {
+        if self.is_held() {
+            return;
+        }
+        if self.config.alternating.is_some() {
+            self.hand_shot_clock(Side::Right);
+            return;
+        }
+        let toggle = self.config.button_toggle || self.config.simultaneous;
+        if self.right_prep.is_some() {
+            if toggle {
+                self.right_prep = None;
+                self.audio_controller.stop();
+            }
+            return;
+        }
+        if self.right_timer.is_running() && toggle {
+            self.stop_side(Side::Right);
+            return;
+        }
+        if !self.config.simultaneous {
+            self.stop_side(Side::Left);
+            self.left_prep = None;
+        }
+        if let Some(ref prep) = self.config.right_timer.prep {
+            if self.right_timer.is_fresh() {
+                let mut prep_timer = Timer::new(Duration::from_secs(prep.time_secs));
+                prep_timer.start();
+                self.right_prep = Some(prep_timer);
+                let pan = self.config.right_timer.pan;
+                match prep.sfx {
+                    Some(ref prep_sfx) => {
+                        self.audio_controller.play_track(prep_sfx, 1.0, false, pan)
+                    }
+                    None => self.audio_controller.stop(),
+                }
+                return;
+            }
+        }
+        self.begin_right_timer();
+    }
+    fn begin_right_timer(&mut self) {
+        let new_end = self.right_timer.is_fresh();
+        self.right_timer.start();
+        self.record("right", TimerEvent::Start, self.right_timer.get_elapsed());
+        self.play_side_music(Side::Right, new_end);
+    }
+    /// Stops a side's timer, recording the stop if it was running
+    fn stop_side(&mut self, side: Side) {
+        let timer = match side {
+            Side::Left => &mut self.left_timer,
+            Side::Right => &mut self.right_timer,
+        };
+        if !timer.is_running() {
+            return;
+        }
+        timer.stop();
+        let elapsed = timer.get_elapsed();
+        self.record(side.name(), TimerEvent::Stop, elapsed);
+    }
+    fn stop_game_timer(&mut self) {
+        if !self.game_timer.is_running() {
+            return;
+        }
+        self.game_timer.stop();
+        self.record("game", TimerEvent::Stop, self.game_timer.get_elapsed());
+    }
+    /// Plays a side's music, moving on to its next track if `advance`
+    fn play_side_music(&mut self, side: Side, advance: bool) {
+        let (timer, playlist) = match side {
+            Side::Left => (&self.config.left_timer, &mut self.left_playlist),
+            Side::Right => (&self.config.right_timer, &mut self.right_playlist),
+        };
+        let Some(ref music) = timer.music_file else {
+            return;
+        };
+        if advance || playlist.current().is_none() {
+            playlist.advance(music, timer.shuffle);
+        }
+        if let Some(track) = playlist.current() {
+            self.audio_controller
+                .play_track(track, timer.volume, timer.loop_music, timer.pan);
+        }
+    }
+    /// Pauses or resumes the music without touching the timers
+    pub fn toggle_music(&mut self) {
+        if self.is_held() {
+            return;
+        }
+        self.audio_controller.toggle_music();
+    }
+    /// Moves the running side's music on to its next track
+    pub fn skip_track(&mut self) {
+        let side = match (self.left_timer.is_running(), self.right_timer.is_running()) {
+            (true, _) => Side::Left,
+            (_, true) => Side::Right,
+            _ => return,
+        };
+        self.play_side_music(side, true);
+    }
+    /// Hands a fresh shot clock to the other side once the side holding it presses their
+    /// button. The first press of an end gives the clock to whoever pressed.
+    fn hand_shot_clock(&mut self, pressed: Side) {
+        let Some(ref alternating) = self.config.alternating else {
+            return;
+        };
+        let next = match self.shot_clock {
+            None => pressed,
+            Some(ref clock) if clock.side == pressed => {
+                match pressed {
+                    Side::Left => self.left_arrows += 1,
+                    Side::Right => self.right_arrows += 1,
+                }
+                pressed.other()
+            }
+            Some(_) => return,
+        };
+        let mut timer = Timer::new(Duration::from_secs(alternating.shot_secs));
+        timer.start();
+        self.shot_clock = Some(ShotClock { side: next, timer });
+        if let Some(ref arrow_sfx) = alternating.arrow_sfx {
+            self.audio_controller.play_file(arrow_sfx);
+        }
+    }
+    fn advance_shot_clock(&mut self) {
+        let (Some(ref alternating), Some(ref mut clock)) =
+            (&self.config.alternating, &mut self.shot_clock)
+        else {
+            return;
+        };
+        if clock.timer.is_running() && clock.timer.get_remaining().is_zero() {
+            clock.timer.stop();
+            match alternating.timeout_sfx {
+                Some(ref timeout_sfx) => self.audio_controller.play_file(timeout_sfx),
+                None => self.audio_controller.stop(),
+            }
+        }
+    }
+    /// Shot clock time for a side in alternating mode. The waiting side shows a full clock.
+    pub fn shot_clock_remaining(&self, side: Side) -> Option<Duration> {
+        let alternating = self.config.alternating.as_ref()?;
+        return Some(match self.shot_clock {
+            Some(ref clock) if clock.side == side => clock.timer.get_remaining(),
+            _ => Duration::from_secs(alternating.shot_secs),
+        });
+    }
+    /// Whether scores are being kept, so arrow values typed on the keyboard go to them
+    pub fn is_scoring(&self) -> bool {
+        return self.scoreboard.is_some();
+    }
+    pub fn score_arrow(&mut self, arrow: Arrow) {
+        if let Some(ref mut scoreboard) = self.scoreboard {
+            scoreboard.enter(arrow);
+        }
+    }
+    pub fn undo_arrow(&mut self) {
+        if let Some(ref mut scoreboard) = self.scoreboard {
+            scoreboard.undo();
+        }
+    }
+    pub fn switch_scoring_side(&mut self) {
+        if let Some(ref mut scoreboard) = self.scoreboard {
+            scoreboard.switch_side();
+        }
+    }
+
+    /// Arrows shot by each side in alternating mode
+    pub fn arrow_counts(&self) -> Option<(u32, u32)> {
+        self.config.alternating.as_ref()?;
+        return Some((self.left_arrows, self.right_arrows));
+    }
+
+    /// Grants extra time after an equipment failure. Running countdown sides get the time
+    /// directly; otherwise it goes to the game timer.
+    pub fn grant_extra_time(&mut self) {
+        let extra = Duration::from_secs(self.config.extra_time_secs);
+        let mut granted = false;
+        // Judges usually call a hold first, so held timers count as running
+        let held = self.held;
+        let left_active = self.left_timer.is_running() || held.is_some_and(|held| held.left);
+        let right_active = self.right_timer.is_running() || held.is_some_and(|held| held.right);
+        let game_active = self.game_timer.is_running() || held.is_some_and(|held| held.game);
+        if left_active && self.left_timer.extend(extra) {
+            log::info!("Granted {}s extra time to the left side", extra.as_secs());
+            granted = true;
+        }
+        if right_active && self.right_timer.extend(extra) {
+            log::info!("Granted {}s extra time to the right side", extra.as_secs());
+            granted = true;
+        }
+        if !granted && game_active {
+            self.game_timer.extend(extra);
+            log::info!("Granted {}s extra time on the game timer", extra.as_secs());
+            granted = true;
+        }
+        if !granted {
+            log::info!("No running timer to grant extra time to");
+        }
+    }
+
+    fn apply_preset(&mut self, side: Side) {
+        let (timer_config, preset, timer) = match side {
+            Side::Left => (
+                &self.config.left_timer,
+                self.left_preset,
+                &mut self.left_timer,
+            ),
+            Side::Right => (
+                &self.config.right_timer,
+                self.right_preset,
+                &mut self.right_timer,
+            ),
+        };
+        let time_secs = match preset {
+            Some(index) => self.config.time_presets[index].time_secs,
+            None => timer_config.time_secs,
+        };
+        timer.set_countdown(Duration::from_secs(time_secs));
+    }
+    /// Swaps in a config edited while running. Colors, cues and options apply immediately;
+    /// timers pick up new times once they're idle, and files opened at startup (logs,
+    /// databases, rosters) need a restart.
+    pub fn reload_config(&mut self, config: Config) {
+        if config.volume != self.config.volume {
+            self.audio_controller.set_volume(config.volume);
+        }
+        let renormalize = config.normalize_loudness != self.config.normalize_loudness
+            || config.music_tracks() != self.config.music_tracks();
+        if config.voice_pack != self.config.voice_pack {
+            self.voice_pack = config.voice_pack.as_deref().and_then(load_voice_pack);
+        }
+        if config.keybindings != self.config.keybindings {
+            self.keymap = Keymap::new(&config.keybindings);
+        }
+        // Start changed playlists over
+        if config.left_timer.music_file != self.config.left_timer.music_file {
+            self.left_playlist = Playlist::default();
+        }
+        if config.right_timer.music_file != self.config.right_timer.music_file {
+            self.right_playlist = Playlist::default();
+        }
+        self.audio_controller
+            .set_fade_out(Duration::from_millis(config.fade_out_ms));
+        self.audio_controller.set_duck_level(config.duck_level);
+        self.audio_controller
+            .set_fallback_device(config.audio_fallback_device.clone());
+        self.config = config;
+        if renormalize {
+            self.normalize_music();
+        }
+        if self
+            .round
+            .is_some_and(|round| round >= self.config.rounds.len())
+        {
+            self.round = None;
+        }
+        self.sequence = self.round_sequence();
+        let preset_count = self.config.time_presets.len();
+        for preset in [&mut self.left_preset, &mut self.right_preset] {
+            if preset.is_some_and(|index| index >= preset_count) {
+                *preset = None;
+            }
+        }
+        if self.left_timer.is_fresh() {
+            self.left_timer = SideTimer::new(&self.config.left_timer);
+        }
+        if self.right_timer.is_fresh() {
+            self.right_timer = SideTimer::new(&self.config.right_timer);
+        }
+        self.apply_preset(Side::Left);
+        self.apply_preset(Side::Right);
+        if self.phase == SequencePhase::Idle
+            && !self.game_timer.is_running()
+            && self.game_timer.get_elapsed().is_zero()
+        {
+            self.game_timer = Timer::new(Duration::from_secs(self.config.game_timer.time_secs));
+        }
+        log::info!("Reloaded config from {:?}", self.source.path);
+    }
+
+    /// Options changed at runtime that differ from the config
+    pub fn runtime_changes(&self) -> Vec<SettingChange> {
+        let mut changes = Vec::new();
+        for side in [Side::Left, Side::Right] {
+            let (section, configured) = match side {
+                Side::Left => ("left_timer", &self.config.left_timer.preset),
+                Side::Right => ("right_timer", &self.config.right_timer.preset),
+            };
+            let preset = self.preset_name(side);
+            if preset != configured.as_deref() {
+                changes.push(SettingChange {
+                    description: format!("{side:?} time preset: {}", preset.unwrap_or("none")),
+                    path: vec![section, "preset"],
+                    value: preset.map(serde_yaml::Value::from),
+                });
+            }
+        }
+        let volume = self.audio_controller.volume();
+        if volume != self.config.volume {
+            changes.push(SettingChange {
+                description: format!("Volume: {:.0}%", volume * 100.0),
+                path: vec!["volume"],
+                value: Some(serde_yaml::Value::from(f64::from(volume))),
+            });
+        }
+        if self.show_stats != self.config.show_stats {
+            changes.push(SettingChange {
+                description: format!("Show statistics: {}", self.show_stats),
+                path: vec!["show_stats"],
+                value: Some(serde_yaml::Value::from(self.show_stats)),
+            });
+        }
+        return changes;
+    }
+    /// Resolves the config from its source again, keeping the current one if the result
+    /// has problems
+    fn apply_source(&mut self) -> bool {
+        let config = match self.source.resolve() {
+            Ok(config) => config,
+            Err(err) => {
+                log::error!("Keeping the current config: {err}");
+                return false;
+            }
+        };
+        let problems = validation::validate(&config);
+        if !problems.is_empty() {
+            log::error!(
+                "Keeping the current config, as it has problems:\n{}",
+                problems.join("\n")
+            );
+            return false;
+        }
+        self.reload_config(config);
+        return true;
+    }
+    /// Picks up edits to the config file
+    pub fn reload_source(&mut self) {
+        match self.source.reload() {
+            Ok(()) => {
+                self.apply_source();
+            }
+            Err(err) => log::error!(
+                "Keeping the current config, as {:?} is invalid: {err}",
+                self.source.path
+            ),
+        }
+    }
+    /// Switches to the next profile in the config, wrapping back around to none
+    pub fn cycle_profile(&mut self) {
+        let names = self.source.profile_names();
+        if names.is_empty() {
+            return;
+        }
+        let previous = self.source.profile().map(str::to_owned);
+        let next = match previous {
+            None => Some(names[0].clone()),
+            Some(ref current) => names
+                .iter()
+                .position(|name| name == current)
+                .and_then(|index| names.get(index + 1))
+                .cloned(),
+        };
+        log::info!("Switching to profile {next:?}");
+        self.source.set_profile(next);
+        if !self.apply_source() {
+            self.source.set_profile(previous);
+        }
+    }
+
+    /// Assigns the next archer in the roster to a side, wrapping back around to nobody
+    pub fn cycle_archer(&mut self, side: Side) {
+        if self.roster.is_empty() {
+            return;
+        }
+        let roster_len = self.roster.len();
+        let assignment = match side {
+            Side::Left => &mut self.left_assignment,
+            Side::Right => &mut self.right_assignment,
+        };
+        *assignment = match *assignment {
+            None => Some(0),
+            Some(index) if index + 1 < roster_len => Some(index + 1),
+            Some(_) => None,
+        };
+        log::info!(
+            "{side:?} side assigned to {:?}",
+            self.archer_name(side).unwrap_or_default()
+        );
+    }
+    /// The side's configured target, falling back to its participant list target position
+    pub fn target_name(&self, side: Side) -> Option<String> {
+        let (timer_config, assigned_target) = match side {
+            Side::Left => (
+                &self.config.left_timer,
+                self.config
+                    .participants
+                    .as_ref()
+                    .map(|participants| &participants.left_target),
+            ),
+            Side::Right => (
+                &self.config.right_timer,
+                self.config
+                    .participants
+                    .as_ref()
+                    .map(|participants| &participants.right_target),
+            ),
+        };
+        return match timer_config.target {
+            Some(ref target) => Some(target.clone()),
+            None => assigned_target.map(|target| format!("Target {target}")),
+        };
+    }
+    /// Who's shooting on a side: the archer assigned from the roster, then the side's
+    /// configured name, then the participant list's archer on its target
+    pub fn archer_name(&self, side: Side) -> Option<String> {
+        let (assignment, name, archer) = match side {
+            Side::Left => (
+                self.left_assignment,
+                &self.config.left_timer.name,
+                &self.left_archer,
+            ),
+            Side::Right => (
+                self.right_assignment,
+                &self.config.right_timer.name,
+                &self.right_archer,
+            ),
+        };
+        return match (assignment, name) {
+            (Some(index), _) => Some(self.roster[index].display_name()),
+            (None, Some(name)) => Some(name.clone()),
+            (None, None) => archer.as_ref().map(|archer| archer.name.clone()),
+        };
+    }
+
+    /// Switches a side to the next time preset, wrapping back around to its configured time
+    pub fn cycle_preset(&mut self, side: Side) {
+        if self.config.time_presets.is_empty() || self.side_is_running(side) {
+            return;
+        }
+        let preset_count = self.config.time_presets.len();
+        let preset = match side {
+            Side::Left => &mut self.left_preset,
+            Side::Right => &mut self.right_preset,
+        };
+        *preset = match *preset {
+            None => Some(0),
+            Some(index) if index + 1 < preset_count => Some(index + 1),
+            Some(_) => None,
+        };
+        log::info!(
+            "{side:?} side switched to preset {:?}",
+            self.preset_name(side)
+        );
+        self.apply_preset(side);
+    }
+    pub fn preset_name(&self, side: Side) -> Option<&str> {
+        let preset = match side {
+            Side::Left => self.left_preset,
+            Side::Right => self.right_preset,
+        };
+        return preset.map(|index| self.config.time_presets[index].name.as_str());
+    }
+
+    /// Records the elapsed time of each running side without stopping it
+    pub fn split(&mut self) {
+        if self.left_timer.is_running() {
+            self.left_splits.push(self.left_timer.get_elapsed());
+        }
+        if self.right_timer.is_running() {
+            self.right_splits.push(self.right_timer.get_elapsed());
+        }
+    }
+    /// Starts any side whose count-in has finished
+    fn advance_prep(&mut self) {
+        if self
+            .left_prep
+            .as_ref()
+            .is_some_and(|prep| prep.get_remaining().is_zero())
+        {
+            self.left_prep = None;
+            self.begin_left_timer();
+        }
+        if self
+            .right_prep
+            .as_ref()
+            .is_some_and(|prep| prep.get_remaining().is_zero())
+        {
+            self.right_prep = None;
+            self.begin_right_timer();
+        }
+    }
+    /// Whether a side is counting, including its count-in and shot clock
+    pub fn side_is_running(&self, side: Side) -> bool {
+        if let Some(ref clock) = self.shot_clock {
+            return clock.side == side && clock.timer.is_running();
+        }
+        return match side {
+            Side::Left => self.left_timer.is_running() || self.left_prep.is_some(),
+            Side::Right => self.right_timer.is_running() || self.right_prep.is_some(),
+        };
+    }
+    /// The time to show for a side, taking count-ins and the shot clock into account
+    pub fn side_display(&self, side: Side) -> Duration {
+        if let Some(remaining) = self.shot_clock_remaining(side) {
+            return remaining;
+        }
+        let (prep, timer) = match side {
+            Side::Left => (&self.left_prep, &self.left_timer),
+            Side::Right => (&self.right_prep, &self.right_timer),
+        };
+        return match prep {
+            Some(prep) => prep.get_remaining(),
+            None => timer.get_display(),
+        };
+    }
+    /// Time the side's countdown has used, if it's set to be shown alongside the remaining time
+    pub fn side_elapsed(&self, side: Side) -> Option<Duration> {
+        let (prep, timer, config) = match side {
+            Side::Left => (&self.left_prep, &self.left_timer, &self.config.left_timer),
+            Side::Right => (
+                &self.right_prep,
+                &self.right_timer,
+                &self.config.right_timer,
+            ),
+        };
+        if !config.show_elapsed
+            || !timer.counts_down()
+            || prep.is_some()
+            || self.shot_clock_remaining(side).is_some()
+        {
+            return None;
+        }
+        return Some(timer.get_elapsed());
+    }
+    pub fn side_counts_down(&self, side: Side) -> bool {
+        if self.config.alternating.is_some() {
+            return true;
+        }
+        return match side {
+            Side::Left => self.left_prep.is_some() || self.left_timer.counts_down(),
+            Side::Right => self.right_prep.is_some() || self.right_timer.counts_down(),
+        };
+    }
+    pub fn left_color(&self) -> &str {
+        return match (&self.left_prep, &self.config.left_timer.prep) {
+            (Some(_), Some(prep)) => &prep.color,
+            _ => self
+                .threshold_color(Side::Left)
+                .or(self.phase_color(Side::Left))
+                .unwrap_or(&self.config.left_timer.color),
+        };
+    }
+    pub fn right_color(&self) -> &str {
+        return match (&self.right_prep, &self.config.right_timer.prep) {
+            (Some(_), Some(prep)) => &prep.color,
+            _ => self
+                .threshold_color(Side::Right)
+                .or(self.phase_color(Side::Right))
+                .unwrap_or(&self.config.right_timer.color),
+        };
+    }
+    /// Which part of shooting a side is in, following the sequence while one is running
+    pub fn side_phase(&self, side: Side) -> SidePhase {
+        let (prep, config) = match side {
+            Side::Left => (&self.left_prep, &self.config.left_timer),
+            Side::Right => (&self.right_prep, &self.config.right_timer),
+        };
+        if prep.is_some() {
+            return SidePhase::Prep;
+        }
+        match self.phase {
+            SequencePhase::Idle => {}
+            SequencePhase::Preparation => return SidePhase::Prep,
+            SequencePhase::Shooting => return SidePhase::Shooting,
+            SequencePhase::Warning => return SidePhase::Warning,
+            SequencePhase::EndOfEnd | SequencePhase::Break => return SidePhase::Stopped,
+        }
+        if !self.side_is_running(side) {
+            return SidePhase::Stopped;
+        }
+        let warning_secs = config
+            .phase_colors
+            .as_ref()
+            .map_or(default_warning_secs(), |colors| colors.warning_secs);
+        let warning = self.side_counts_down(side)
+            && self.side_display(side) <= Duration::from_secs(warning_secs);
+        return match warning {
+            true => SidePhase::Warning,
+            false => SidePhase::Shooting,
+        };
+    }
+    /// Color the side's `phase_colors` give its current phase
+    fn phase_color(&self, side: Side) -> Option<&str> {
+        let colors = match side {
+            Side::Left => self.config.left_timer.phase_colors.as_ref()?,
+            Side::Right => self.config.right_timer.phase_colors.as_ref()?,
+        };
+        let color = match self.side_phase(side) {
+            SidePhase::Prep => &colors.prep,
+            SidePhase::Shooting => &colors.shooting,
+            SidePhase::Warning => &colors.warning,
+            SidePhase::Stopped => &colors.stopped,
+        };
+        return color.as_deref();
+    }
+    /// Flashing a side is set up with, while its running countdown is in its final seconds
+    fn side_flashing(&self, side: Side) -> Option<&FlashConfig> {
+        let (prep, timer, config) = match side {
+            Side::Left => (&self.left_prep, &self.left_timer, &self.config.left_timer),
+            Side::Right => (
+                &self.right_prep,
+                &self.right_timer,
+                &self.config.right_timer,
+            ),
+        };
+        let flash = config.flash.as_ref()?;
+        if prep.is_some() || !timer.is_running() || !self.side_counts_down(side) {
+            return None;
+        }
+        let remaining = self.side_display(side);
+        // Overtime flashes in its own way
+        if remaining.is_zero() || remaining >= Duration::from_secs(flash.below_secs) {
+            return None;
+        }
+        return Some(flash);
+    }
+    /// What of a side is flashed off right now, in a running countdown's final seconds
+    pub fn side_flash(&self, side: Side) -> Option<FlashTarget> {
+        let flash = self.side_flashing(side)?;
+        return flash_off(flash, self.side_display(side));
+    }
+    /// Flashing the game timer is set up with, while its running countdown is in its final
+    /// seconds
+    fn game_flashing(&self) -> Option<&FlashConfig> {
+        let flash = self.config.game_timer.flash.as_ref()?;
+        if !self.game_timer.is_running() {
+            return None;
+        }
+        let remaining = self.game_timer.get_remaining();
+        if remaining.is_zero() || remaining >= Duration::from_secs(flash.below_secs) {
+            return None;
+        }
+        return Some(flash);
+    }
+    /// What of the game timer is flashed off right now, in its running countdown's final
+    /// seconds
+    pub fn game_flash(&self) -> Option<FlashTarget> {
+        let flash = self.game_flashing()?;
+        return flash_off(flash, self.game_timer.get_remaining());
+    }
+    /// Whether the center's background is being flashed, which shouldn't be faded
+    pub fn game_flashes_background(&self) -> bool {
+        return self
+            .game_flashing()
+            .is_some_and(|flash| flash.target == FlashTarget::Background);
+    }
+    /// Whether a side's background is being flashed, which shouldn't be faded
+    pub fn side_flashes_background(&self, side: Side) -> bool {
+        return self
+            .side_flashing(side)
+            .is_some_and(|flash| flash.target == FlashTarget::Background);
+    }
+    /// Color of the tightest threshold a counting-down side has dropped below
+    fn threshold_color(&self, side: Side) -> Option<&str> {
+        if !self.side_counts_down(side) {
+            return None;
+        }
+        let thresholds = match side {
+            Side::Left => &self.config.left_timer.thresholds,
+            Side::Right => &self.config.right_timer.thresholds,
+        };
+        let remaining = self.side_display(side);
+        return thresholds
+            .iter()
+            .filter(|threshold| remaining < Duration::from_secs(threshold.below_secs))
+            .min_by_key(|threshold| threshold.below_secs)
+            .map(|threshold| threshold.color.as_str());
+    }
+
+    /// Switches the shooting sequence to `phase`, resetting the game timer and playing its cue
+    fn enter_phase(&mut self, phase: SequencePhase) {
+        let Some(sequence) = self.sequence.clone() else {
+            return;
+        };
+        self.phase = phase;
+        self.log_phase(phase);
+        match phase {
+            SequencePhase::Idle => {}
+            SequencePhase::Preparation => {
+                self.game_timer = Timer::new(Duration::from_secs(sequence.prep_secs));
+                self.game_timer.start();
+                match sequence.prep_sfx {
+                    Some(ref prep_sfx) => self.audio_controller.play_file(prep_sfx),
+                    None => self.audio_controller.stop(),
+                }
+            }
+            SequencePhase::Shooting => {
+                self.game_timer = Timer::new(Duration::from_secs(sequence.shooting_secs));
+                self.game_timer.start();
+                match sequence.shooting_sfx {
+                    Some(ref shooting_sfx) => self.audio_controller.play_file(shooting_sfx),
+                    None => self.audio_controller.stop(),
+                }
+            }
+            SequencePhase::Warning => {}
+            SequencePhase::Break => {
+                let break_secs = self
+                    .round
+                    .and_then(|round| self.config.rounds[round].break_secs)
+                    .unwrap_or_default();
+                self.game_timer = Timer::new(Duration::from_secs(break_secs));
+                self.game_timer.start();
+            }
+            SequencePhase::EndOfEnd => {
+                self.stop_game_timer();
+                self.stop_side(Side::Left);
+                self.stop_side(Side::Right);
+                self.left_prep = None;
+                self.right_prep = None;
+                match sequence.end_sfx {
+                    Some(ref end_sfx) => self.audio_controller.play_file(end_sfx),
+                    None => self.audio_controller.stop(),
+                }
+            }
+        }
+        match phase {
+            SequencePhase::Preparation => self.sound_horn(|horn| horn.to_line_blasts),
+            SequencePhase::Shooting => {
+                self.sound_horn(|horn| horn.start_blasts);
+                self.signal_buzzer(BuzzerSignal::Start);
+            }
+            SequencePhase::EndOfEnd => {
+                self.sound_horn(|horn| horn.end_blasts);
+                self.signal_buzzer(BuzzerSignal::Stop);
+            }
+            _ => {}
+        }
+        let text = self
+            .config
+            .speech
+            .as_ref()
+            .and_then(|speech| speech.phase_text(phase));
+        if let Some(text) = text.map(str::to_owned) {
+            self.say(&text);
+        }
+    }
+    /// Announces `text`, with `{end}` replaced by the end number
+    fn say(&mut self, text: &str) {
+        let Some(ref speech) = self.config.speech else {
+            return;
+        };
+        let text = text.replace("{end}", &self.end_number.to_string());
+        self.audio_controller.speak(&speech.command, text);
+    }
+    /// Plays the voice pack's clips and the milestone sounds as the countdowns pass them
+    fn advance_countdown_cues(&mut self) {
+        // Remaining time each countdown has run down from and to since the last tick
+        let mut passed = Vec::new();
+        let remaining = self.game_timer.get_remaining();
+        if let Some(from) = self.game_watch.update(remaining) {
+            passed.push((from, remaining));
+        }
+        for (timer, watch) in [
+            (&self.left_timer, &mut self.left_watch),
+            (&self.right_timer, &mut self.right_watch),
+        ] {
+            if timer.counts_down() {
+                let remaining = timer.get_display();
+                if let Some(from) = watch.update(remaining) {
+                    passed.push((from, remaining));
+                }
+            }
+        }
+        // Timers passing the same time together only need sounding once
+        let clip = self
+            .voice_pack
+            .as_ref()
+            .and_then(|voice_pack| {
+                passed
+                    .iter()
+                    .find_map(|&(from, to)| voice_pack.clip_between(from, to))
+            })
+            .map(Path::to_path_buf);
+        if let Some(clip) = clip {
+            self.audio_controller.play_cue(&clip, true);
+        }
+        let milestone = self.config.milestones.iter().find(|milestone| {
+            let at = Duration::from_secs(milestone.at_secs);
+            return passed.iter().any(|&(from, to)| from > at && to <= at);
+        });
+        match milestone.map(|milestone| milestone.sound.clone()) {
+            Some(Some(sound)) => self.audio_controller.play_cue(&sound, true),
+            Some(None) => self.audio_controller.play_beep(),
+            None => {}
+        }
+    }
+    /// Measures the music for loudness normalization, if it's enabled
+    fn normalize_music(&mut self) {
+        let tracks = match self.config.normalize_loudness {
+            true => Some(self.config.music_tracks()),
+            false => None,
+        };
+        self.audio_controller.normalize(tracks);
+    }
+    /// Keeps the metronome clicking while it's on and a timer is running
+    fn advance_metronome(&mut self) {
+        let running = (self.game_timer.is_running() && !self.game_timer.get_remaining().is_zero())
+            || self.left_timer.is_running()
+            || self.right_timer.is_running();
+        let interval = self
+            .config
+            .metronome
+            .as_ref()
+            .and_then(MetronomeConfig::interval)
+            .filter(|_| self.metronome_on && running);
+        match interval {
+            Some(interval) => self.audio_controller.start_metronome(interval),
+            None => self.audio_controller.stop_metronome(),
+        }
+    }
+    pub fn toggle_metronome(&mut self) {
+        if self.config.metronome.is_none() {
+            return;
+        }
+        self.metronome_on = !self.metronome_on;
+        log::info!(
+            "Metronome {}",
+            match self.metronome_on {
+                true => "on",
+                false => "off",
+            }
+        );
+    }
+    /// Makes the remaining time announcements as the game timer passes them
+    fn advance_announcements(&mut self) {
+        let Some(ref speech) = self.config.speech else {
+            return;
+        };
+        let counting = self.game_timer.is_running()
+            && !matches!(
+                self.phase,
+                SequencePhase::Preparation | SequencePhase::Break
+            );
+        if !counting {
+            self.announced_remaining = None;
+            return;
+        }
+        let remaining = self.game_timer.get_remaining();
+        let due: Vec<String> = match self.announced_remaining {
+            Some(previous) => speech
+                .remaining
+                .iter()
+                .filter(|announcement| {
+                    let at = Duration::from_secs(announcement.at_secs);
+                    return previous > at && remaining <= at;
+                })
+                .map(|announcement| announcement.text.clone())
+                .collect(),
+            None => Vec::new(),
+        };
+        self.announced_remaining = Some(remaining);
+        for text in due {
+            self.say(&text);
+        }
+    }
+    /// Sounds a range signal on the horn, if there is one
+    fn sound_horn(&mut self, blasts: impl Fn(&HornConfig) -> u32) {
+        if let Some(ref horn) = self.config.horn {
+            self.audio_controller.play_horn(horn, blasts(horn));
+        }
+    }
+    /// Sounds one of the GPIO buzzer's patterns, if it's open
+    fn signal_buzzer(&mut self, signal: BuzzerSignal) {
+        if let Some(ref sender) = self.buzzer_signals {
+            if sender.unbounded_send(signal).is_err() {
+                self.buzzer_signals = None;
+            }
+        }
+    }
+    fn advance_sequence(&mut self) {
+        let Some(ref sequence) = self.sequence else {
+            return;
+        };
+        let remaining = self.game_timer.get_remaining();
+        match self.phase {
+            SequencePhase::Preparation if remaining.is_zero() => {
+                self.enter_phase(SequencePhase::Shooting);
+            }
+            SequencePhase::Shooting if remaining <= Duration::from_secs(sequence.warning_secs) => {
+                self.enter_phase(SequencePhase::Warning);
+            }
+            SequencePhase::Warning if remaining.is_zero() => {
+                self.finish_line();
+            }
+            SequencePhase::EndOfEnd if self.has_break() => {
+                self.enter_phase(SequencePhase::Break);
+            }
+            SequencePhase::Break if remaining.is_zero() => {
+                self.start_next_end();
+            }
+            _ => {}
+        }
+    }
+    fn start_next_end(&mut self) {
+        if self.end_started {
+            self.end_number += 1;
+        }
+        self.end_started = true;
+        self.line_index = 0;
+        self.enter_phase(SequencePhase::Preparation);
+    }
+    /// Whether the selected round runs a timed break before its next end
+    fn has_break(&self) -> bool {
+        let Some(round) = self.round else {
+            return false;
+        };
+        return self.config.rounds[round].break_secs.is_some() && !self.is_round_complete();
+    }
+    pub fn is_round_complete(&self) -> bool {
+        let Some(round) = self.round else {
+            return false;
+        };
+        return self.end_started
+            && self.end_number >= self.config.rounds[round].ends as usize
+            && matches!(self.phase, SequencePhase::Idle | SequencePhase::EndOfEnd);
+    }
+    /// Dials the shooting time up or down by `steps` encoder clicks while the timers are idle
+    pub fn adjust_time(&mut self, steps: i32) {
+        let Some(ref encoder) = self.config.gpio.encoder else {
+            return;
+        };
+        if self.game_timer.is_running() || self.is_held() || self.config.intervals.is_some() {
+            return;
+        }
+        let adjust = |secs: u64| {
+            let change = encoder.step_secs * u64::from(steps.unsigned_abs());
+            return match steps < 0 {
+                true => secs.saturating_sub(change).max(encoder.step_secs),
+                false => secs + change,
+            };
+        };
+        let secs = match self.sequence {
+            Some(ref mut sequence) => {
+                sequence.shooting_secs = adjust(sequence.shooting_secs);
+                sequence.shooting_secs
+            }
+            None => {
+                let secs = adjust(self.game_timer.get_duration().as_secs());
+                self.game_timer.set_duration(Duration::from_secs(secs));
+                secs
+            }
+        };
+        log::info!("Shooting time dialed to {secs}s");
+        self.time_adjusted = Some((Instant::now(), Duration::from_secs(secs)));
+    }
+    /// The shooting time just dialed in, while it should still be shown
+    pub fn time_adjustment(&self) -> Option<Duration> {
+        let (adjusted_at, duration) = self.time_adjusted?;
+        return (adjusted_at.elapsed() < ADJUSTMENT_SHOWN).then_some(duration);
+    }
+
+    /// Selects a round template, applying its timings to the shooting sequence
+    pub fn select_round(&mut self, round: Option<usize>) {
+        self.clear_timers();
+        self.round = round;
+        self.end_number = 1;
+        self.end_started = false;
+        match round {
+            Some(round) => log::info!("Selected round {:?}", self.config.rounds[round].name),
+            None => log::info!("Cleared round selection"),
+        }
+        self.sequence = self.round_sequence();
+    }
+    /// The configured sequence with the selected round's timings applied
+    fn round_sequence(&self) -> Option<SequenceConfig> {
+        let Some(round) = self.round else {
+            return self.config.sequence.clone();
+        };
+        let template = &self.config.rounds[round];
+        let mut sequence = self.config.sequence.clone().unwrap_or_default();
+        if let Some(prep_secs) = template.prep_secs {
+            sequence.prep_secs = prep_secs;
+        }
+        sequence.shooting_secs = template
+            .shooting_secs
+            .unwrap_or(u64::from(template.arrows_per_end) * 40);
+        return Some(sequence);
+    }
+    pub fn select_round_by_name(&mut self, name: &str) {
+        match self
+            .config
+            .rounds
+            .iter()
+            .position(|round| round.name == name)
+        {
+            Some(index) => self.select_round(Some(index)),
+            None => log::warn!("Unknown round {name:?}"),
+        }
+    }
+    /// Steps through the round templates, ending back at no round
+    pub fn cycle_round(&mut self) {
+        if self.config.rounds.is_empty() || self.game_timer.is_running() || self.is_held() {
+            return;
+        }
+        let next = match self.round {
+            None => Some(0),
+            Some(index) if index + 1 < self.config.rounds.len() => Some(index + 1),
+            Some(_) => None,
+        };
+        self.select_round(next);
+    }
+    /// Calls up the next line of the current end, or ends the end if every line has shot
+    fn finish_line(&mut self) {
+        let line_count = match self.sequence {
+            Some(ref sequence) => sequence.lines.len(),
+            None => 0,
+        };
+        if self.line_index + 1 < line_count {
+            self.line_index += 1;
+            self.enter_phase(SequencePhase::Preparation);
+        } else {
+            self.enter_phase(SequencePhase::EndOfEnd);
+        }
+    }
+    fn advance_pending_start(&mut self) {
+        if self.is_held() {
+            return;
+        }
+        if self
+            .pending_start
+            .is_some_and(|pending_start| Instant::now() >= pending_start)
+        {
+            self.begin_game_timer();
+        }
+    }
+    pub fn is_start_pending(&self) -> bool {
+        return self.pending_start.is_some();
+    }
+    fn enter_interval(&mut self, interval: IntervalState) {
+        let Some(ref intervals) = self.config.intervals else {
+            return;
+        };
+        let (secs, music) = match interval.resting {
+            false => (intervals.shoot_secs, &intervals.shoot_music),
+            true => (intervals.rest_secs, &intervals.rest_music),
+        };
+        self.interval = Some(interval);
+        self.game_timer = Timer::new(Duration::from_secs(secs));
+        self.game_timer.start();
+        match music {
+            Some(ref music) => self.audio_controller.play_track(music, 1.0, true, 0.0),
+            None => self.audio_controller.stop(),
+        }
+        self.signal_buzzer(match interval.resting {
+            false => BuzzerSignal::Start,
+            true => BuzzerSignal::Stop,
+        });
+    }
+    fn advance_intervals(&mut self) {
+        let (Some(ref intervals), Some(interval)) = (&self.config.intervals, self.interval) else {
+            return;
+        };
+        if !self.game_timer.get_remaining().is_zero() {
+            return;
+        }
+        if interval.resting {
+            self.enter_interval(IntervalState {
+                round: interval.round + 1,
+                resting: false,
+            });
+        } else if interval.round < intervals.repeats {
+            self.enter_interval(IntervalState {
+                round: interval.round,
+                resting: true,
+            });
+        } else {
+            self.interval = None;
+            self.freeze();
+        }
+    }
+    /// Stops everything and sounds the end signals once the game timer runs out, when no
+    /// sequence or intervals are moving it on. Sides can't be run past the end either.
+    fn advance_game_timer(&mut self) {
+        if self.sequence.is_some() || self.interval.is_some() {
+            return;
+        }
+        if self.game_timer.get_remaining().is_zero()
+            && (self.game_timer.is_running()
+                || self.left_timer.is_running()
+                || self.right_timer.is_running())
+        {
+            self.freeze();
+        }
+    }
+    /// Text shown beneath the game timer: the shooting line or interval progress
+    pub fn center_caption(&self) -> Option<String> {
+        if let (Some(ref intervals), Some(interval)) = (&self.config.intervals, self.interval) {
+            let activity = if interval.resting { "Rest" } else { "Shoot" };
+            return Some(format!(
+                "{activity} {}/{}",
+                interval.round, intervals.repeats
+            ));
+        }
+        if self.is_start_pending() {
+            return Some(String::from("Ready"));
+        }
+        let end = self.end_caption();
+        return Some(match self.current_line() {
+            Some(line) => format!("{end} {line}"),
+            None => end,
+        });
+    }
+    /// Current end, out of the round's ends if one is selected
+    pub fn end_caption(&self) -> String {
+        return match self.round {
+            Some(round) => format!("End {}/{}", self.end_number, self.config.rounds[round].ends),
+            None => format!("End {}", self.end_number),
+        };
+    }
+    /// Designation of the line currently on the shooting line, if rotation is configured
+    pub fn current_line(&self) -> Option<&str> {
+        let sequence = self.sequence.as_ref()?;
+        if sequence.lines.is_empty() || self.phase == SequencePhase::Idle {
+            return None;
+        }
+        let rotation = self.end_number.saturating_sub(1);
+        let index = (rotation + self.line_index) % sequence.lines.len();
+        return Some(&sequence.lines[index]);
+    }
+    /// Colors as shown, with the theme applied
+    pub fn themed_colors(&self, color: &str, text_color: &str) -> (String, String) {
+        return match self.config.theme {
+            Some(theme) => theme.apply(color, text_color),
+            None => (color.to_owned(), text_color.to_owned()),
+        };
+    }
+    /// Background color of the center panel for the current sequence phase
+    pub fn center_color(&self) -> &str {
+        if let (Some(ref intervals), Some(interval)) = (&self.config.intervals, self.interval) {
+            return match interval.resting {
+                false => &intervals.shoot_color,
+                true => &intervals.rest_color,
+            };
+        }
+        let Some(ref sequence) = self.sequence else {
+            return &self.config.game_timer.color;
+        };
+        return match self.phase {
+            SequencePhase::Idle => &self.config.game_timer.color,
+            SequencePhase::Preparation => &sequence.prep_color,
+            SequencePhase::Shooting => &sequence.shooting_color,
+            SequencePhase::Warning => &sequence.warning_color,
+            SequencePhase::EndOfEnd | SequencePhase::Break => &sequence.end_color,
+        };
+    }
+
+    /// How the app is doing, for the status LED
+    pub fn health(&self) -> Health {
+        if self.gpio_error.is_some() {
+            return Health::GpioFault;
+        }
+        if self.audio_controller.fault().is_some() {
+            return Health::AudioFault;
+        }
+        return Health::Running;
+    }
+    /// What a range's light stack should show: green while shooting, amber in the warning
+    /// period and red otherwise
+    pub fn light(&self) -> Light {
+        if let Some(interval) = self.interval {
+            return match interval.resting {
+                false => Light::Green,
+                true => Light::Red,
+            };
+        }
+        if self.sequence.is_none() {
+            return countdown_light(
+                &self.game_timer,
+                Duration::from_secs(self.config.game_timer.warning_secs),
+            );
+        }
+        return match self.phase {
+            SequencePhase::Shooting => Light::Green,
+            SequencePhase::Warning => Light::Amber,
+            SequencePhase::Idle
+            | SequencePhase::Preparation
+            | SequencePhase::EndOfEnd
+            | SequencePhase::Break => Light::Red,
+        };
+    }
+
+    /// Saves the time each side used before the timers are cleared
+    fn record_end(&mut self) {
+        let left = self.left_timer.get_elapsed();
+        let right = self.right_timer.get_elapsed();
+        if left.is_zero() && right.is_zero() {
+            return;
+        }
+        let record = EndRecord {
+            end: self.end_number,
+            left: (!left.is_zero()).then_some(left),
+            right: (!right.is_zero()).then_some(right),
+        };
+        #[cfg(feature = "sqlite")]
+        if let Some(ref store) = self.store {
+            store.record_end(&record);
+        }
+        self.session.record(record);
+    }
+    /// Resets the timers and closes out the session, keeping its ends up for review
+    pub fn finish_session(&mut self) {
+        self.reset();
+        self.export_results();
+        if !self.session.ends().is_empty() {
+            self.history.push(self.current_session());
+            self.session = Session::new();
+            self.show_summary = true;
+        }
+        if let Some(ref mut scoreboard) = self.scoreboard {
+            scoreboard.clear();
+        }
+    }
+    fn export_results(&self) {
+        let Some(ParticipantsConfig {
+            export_dir: Some(ref export_dir),
+            ref left_target,
+            ref right_target,
+            ..
+        }) = self.config.participants
+        else {
+            return;
+        };
+        if self.session.ends().is_empty() {
+            return;
+        }
+        let path = export_dir.join(format!(
+            "results-{}.txt",
+            chrono::Local::now().format("%Y%m%d-%H%M%S")
+        ));
+        let scores = self.scoreboard.as_ref().map(|scoreboard| {
+            return (scoreboard.side(Side::Left), scoreboard.side(Side::Right));
+        });
+        match participants::export_results(
+            &path,
+            &self.session,
+            scores,
+            (left_target, right_target),
+            (
+                self.archer_name(Side::Left).as_deref(),
+                self.archer_name(Side::Right).as_deref(),
+            ),
+        ) {
+            Ok(()) => log::info!("Exported results to {path:?}"),
+            Err(err) => log::error!("Failed to export results to {path:?}: {err}"),
+        }
+    }
+    fn current_session(&self) -> FinishedSession {
+        return FinishedSession {
+            finished_at: chrono::Local::now(),
+            session: self.session.clone(),
+            scores: self.scoreboard.as_ref().map(|scoreboard| {
+                return (
+                    scoreboard.side(Side::Left).clone(),
+                    scoreboard.side(Side::Right).clone(),
+                );
+            }),
+            archers: (self.archer_name(Side::Left), self.archer_name(Side::Right)),
+        };
+    }
+    /// The session shown on the summary screen, if it's up
+    pub fn summary(&self) -> Option<&Session> {
+        if !self.show_summary {
+            return None;
+        }
+        return self.history.last().map(|finished| &finished.session);
+    }
+    /// Saves today's sessions, including the one in progress, as an HTML report beside the
+    /// config file
+    pub fn save_report(&self) {
+        let today = chrono::Local::now().date_naive();
+        let mut sessions: Vec<&FinishedSession> = self
+            .history
+            .iter()
+            .filter(|finished| finished.finished_at.date_naive() == today)
+            .collect();
+        let current = self.current_session();
+        if !self.session.ends().is_empty() {
+            sessions.push(&current);
+        }
+        let title = format!("Archery timer report {today}");
+        let path = self
+            .source
+            .path
+            .with_file_name(format!("report-{}.html", today.format("%Y-%m-%d")));
+        match report::save(&path, &title, &sessions) {
+            Ok(()) => log::info!("Saved report to {path:?}"),
+            Err(err) => log::error!("Failed to save report to {path:?}: {err}"),
+        }
+    }
+    pub fn dismiss_summary(&mut self) {
+        self.show_summary = false;
+    }
+    pub fn toggle_stats(&mut self) {
+        self.show_stats = !self.show_stats;
+    }
+    /// Turns the master volume up or down by `steps` tenths
+    pub fn change_volume(&mut self, steps: i32) {
+        let volume = self.audio_controller.volume() * 10.0 + steps as f32;
+        let volume = (volume.round() / 10.0).clamp(0.0, 1.0);
+        log::info!("Volume set to {:.0}%", volume * 100.0);
+        self.audio_controller.set_volume(volume);
+    }
+
+    /// Moves on to the next end without shooting the current one
+    pub fn advance_end(&mut self) {
+        self.end_number += 1;
+        self.end_started = false;
+    }
+    pub fn rewind_end(&mut self) {
+        self.end_number = self.end_number.saturating_sub(1).max(1);
+        self.end_started = false;
+    }
+    /// The operator's reset action, which can also roll the end counter forward
+    pub fn reset(&mut self) {
+        self.clear_timers();
+        self.signal_buzzer(BuzzerSignal::Reset);
+        if self.config.reset_advances_end {
+            self.advance_end();
+        }
+    }
+
+    /// Starts the game timer at the next occurrence of `time`
+    pub fn schedule_start(&mut self, time: chrono::NaiveTime) {
+        let start = next_occurrence(time);
+        log::info!("Game timer scheduled to start at {start}");
+        self.scheduled_start = Some(start);
+    }
+    pub fn cancel_scheduled_start(&mut self) {
+        self.scheduled_start = None;
+    }
+    pub fn time_until_start(&self) -> Option<Duration> {
+        let start = self.scheduled_start?;
+        let now = chrono::Local::now().naive_local();
+        return Some((start - now).to_std().unwrap_or_default());
+    }
+    fn advance_schedule(&mut self) {
+        if self
+            .time_until_start()
+            .is_some_and(|remaining| remaining.is_zero())
+        {
+            self.scheduled_start = None;
+            self.start_game_timer();
+        }
+    }
+
+    /// Advances time-driven state. Called from the UI refresh loop.
+    pub fn tick(&mut self) {
+        self.advance_schedule();
+        self.advance_pending_start();
+        self.advance_sequence();
+        self.advance_intervals();
+        self.advance_game_timer();
+        self.advance_prep();
+        self.advance_shot_clock();
+        self.advance_countdown_cues();
+        self.check_countdowns();
+        self.advance_announcements();
+        self.advance_metronome();
+        self.audio_controller.recover();
+        self.audio_controller.update_ducking();
+        if let Some(failure) = self.audio_controller.take_failure() {
+            self.toast = Some((failure, Instant::now()));
+        }
+        self.advance_idle();
+        self.play_cue();
+        self.save_checkpoint();
+    }
+
+    fn advance_idle(&mut self) {
+        let idle = !self.game_timer.is_running()
+            && !self.side_is_running(Side::Left)
+            && !self.side_is_running(Side::Right)
+            && !self.is_start_pending()
+            && self.held.is_none();
+        match (idle, self.idle_since) {
+            (true, None) => self.idle_since = Some(Instant::now()),
+            (false, Some(_)) => self.idle_since = None,
+            _ => {}
+        }
+    }
+    /// How far the side buttons have been held towards a reset, from 0 to 1
+    pub fn reset_hold_progress(&self) -> Option<f64> {
+        let started = self.reset_hold_started?;
+        let hold = Duration::from_millis(self.config.reset_hold_ms?);
+        return Some((started.elapsed().as_secs_f64() / hold.as_secs_f64()).min(1.0));
+    }
+    /// Time of day to show while the timers have been stopped for long enough
+    pub fn wall_clock(&self) -> Option<String> {
+        let wall_clock = self.config.wall_clock.as_ref()?;
+        let idle_since = self.idle_since?;
+        if idle_since.elapsed() < Duration::from_secs(wall_clock.idle_secs) {
+            return None;
+        }
+        return Some(chrono::Local::now().format(&wall_clock.format).to_string());
+    }
+
+    /// The passing problem to show, until it's been up for a while
+    pub fn toast(&self) -> Option<&str> {
+        return self
+            .toast
+            .as_ref()
+            .filter(|(_, shown)| shown.elapsed() < TOAST_DURATION)
+            .map(|(message, _)| message.as_str());
+    }
+
+    fn save_checkpoint(&mut self) {
+        let Some(ref path) = self.config.checkpoint else {
+            return;
+        };
+        let interval = Duration::from_secs(self.config.checkpoint_interval_secs);
+        if let Some(last_checkpoint) = self.last_checkpoint {
+            if last_checkpoint.elapsed() < interval {
+                return;
+            }
+        }
+        self.last_checkpoint = Some(Instant::now());
+        let side_checkpoint = |timer: &SideTimer| TimerCheckpoint {
+            running: timer.is_running(),
+            elapsed: timer.get_elapsed(),
+            duration: timer.get_countdown(),
+            extra: timer.get_extra(),
+        };
+        let checkpoint = Checkpoint {
+            saved_at_ms: chrono::Local::now().timestamp_millis(),
+            round: self.round,
+            phase: self.phase,
+            end_number: self.end_number,
+            end_started: self.end_started,
+            line_index: self.line_index,
+            game: TimerCheckpoint {
+                running: self.game_timer.is_running(),
+                elapsed: self.game_timer.get_elapsed(),
+                duration: Some(self.game_timer.get_duration()),
+                extra: self.game_timer.get_extra(),
+            },
+            left: side_checkpoint(&self.left_timer),
+            right: side_checkpoint(&self.right_timer),
+        };
+        checkpoint.save(path);
+    }
+    /// Saves the timers and logs and returns the command that shuts the system down, e.g. on a
+    /// power button or when a UPS reports its battery running out. The command is left for the
+    /// caller to run, as it can take a while and mustn't hold up anything waiting on the state.
+    pub fn shutdown_system(&mut self) -> Option<std::process::Command> {
+        if self.shutting_down {
+            return None;
+        }
+        log::warn!("Shutdown requested, saving state");
+        if let Some(ref event_log) = self.event_log {
+            event_log.sync();
+        }
+        self.last_checkpoint = None;
+        self.save_checkpoint();
+        log::logger().flush();
+        let Some((program, args)) = self.config.gpio.shutdown_command.split_first() else {
+            log::error!("gpio.shutdown_command is empty, not shutting down");
+            return None;
+        };
+        self.shutting_down = true;
+        let mut command = std::process::Command::new(program);
+        command.args(args);
+        return Some(command);
+    }
+    /// Picks up from the last checkpoint, counting the time spent down against any timers
+    /// that were running
+    pub fn restore_checkpoint(&mut self) {
+        let Some(checkpoint) = self.config.checkpoint.as_deref().and_then(Checkpoint::load) else {
+            return;
+        };
+        let downtime = checkpoint.downtime();
+        log::info!(
+            "Restoring end {} from a checkpoint {}s old",
+            checkpoint.end_number,
+            downtime.as_secs()
+        );
+        if checkpoint
+            .round
+            .is_some_and(|round| round < self.config.rounds.len())
+        {
+            self.select_round(checkpoint.round);
+        }
+        self.phase = checkpoint.phase;
+        self.end_number = checkpoint.end_number;
+        self.end_started = checkpoint.end_started;
+        self.line_index = checkpoint.line_index;
+        let elapsed = |timer: &TimerCheckpoint| match timer.running {
+            true => timer.elapsed + downtime,
+            false => timer.elapsed,
+        };
+        if let Some(duration) = checkpoint.game.duration {
+            self.game_timer.set_duration(duration);
+        }
+        self.game_timer.extend(checkpoint.game.extra);
+        self.game_timer
+            .restore(elapsed(&checkpoint.game), checkpoint.game.running);
+        if let Some(duration) = checkpoint.left.duration {
+            self.left_timer.set_countdown(duration);
+        }
+        self.left_timer.extend(checkpoint.left.extra);
+        self.left_timer
+            .restore(elapsed(&checkpoint.left), checkpoint.left.running);
+        if let Some(duration) = checkpoint.right.duration {
+            self.right_timer.set_countdown(duration);
+        }
+        self.right_timer.extend(checkpoint.right.extra);
+        self.right_timer
+            .restore(elapsed(&checkpoint.right), checkpoint.right.running);
+    }
+
+    /// Logs a timer event as it happens, and queues its cue
+    fn record(&mut self, timer: &str, event: TimerEvent, elapsed: Duration) {
+        if let Some(ref mut event_log) = self.event_log {
+            event_log.write(self.end_number, timer, event.name(), elapsed);
+        }
+        self.cue(event);
+    }
+    fn log_phase(&mut self, phase: SequencePhase) {
+        if let Some(ref mut event_log) = self.event_log {
+            let phase = format!("{phase:?}").to_lowercase();
+            event_log.write(self.end_number, "sequence", &phase, Duration::ZERO);
+        }
+    }
+    /// Queues the cue for a timer event, to be sounded on the next tick
+    fn cue(&mut self, event: TimerEvent) {
+        self.cue = self.cue.max(Some(event));
+    }
+    /// Sounds the start, stop or reset cue for the timer events since the last tick. A side
+    /// starting while the other stops only sounds the start.
+    fn play_cue(&mut self) {
+        let cue = match self.cue.take() {
+            Some(TimerEvent::Start) => &self.config.start_sound,
+            Some(TimerEvent::Clear) => &self.config.reset_sound,
+            Some(TimerEvent::Stop) => &self.config.stop_sound,
+            None => return,
+        };
+        if let Some(ref cue) = cue {
+            self.audio_controller
+                .play_cue(cue, self.config.cues_over_music);
+        }
+    }
+
+    /// Sounds the end cue for any countdowns that have run out, stopping them unless
+    /// they are allowed to run into overtime
+    pub fn check_countdowns(&mut self) {
+        if !self.left_timer.is_expired() {
+            self.left_alerted = false;
+        } else if self.left_timer.is_running() && !self.left_alerted {
+            if self.left_timer.allows_overtime() {
+                self.left_alerted = true;
+            } else {
+                self.stop_side(Side::Left);
+            }
+            let timer = &self.config.left_timer;
+            match timer.end_sfx {
+                Some(ref end_sfx) => self
+                    .audio_controller
+                    .play_track(end_sfx, 1.0, false, timer.pan),
+                None => self.audio_controller.stop(),
+            }
+        }
+        if !self.right_timer.is_expired() {
+            self.right_alerted = false;
+        } else if self.right_timer.is_running() && !self.right_alerted {
+            if self.right_timer.allows_overtime() {
+                self.right_alerted = true;
+            } else {
+                self.stop_side(Side::Right);
+            }
+            let timer = &self.config.right_timer;
+            match timer.end_sfx {
+                Some(ref end_sfx) => self
+                    .audio_controller
+                    .play_track(end_sfx, 1.0, false, timer.pan),
+                None => self.audio_controller.stop(),
+            }
+        }
+    }
+
+    pub fn is_held(&self) -> bool {
+        return self.held.is_some();
+    }
+    /// Freezes every running timer and the audio for a judge's hold, or resumes them
+    pub fn toggle_hold(&mut self) {
+        match self.held.take() {
+            Some(held) => {
+                if held.game {
+                    self.game_timer.start();
+                    self.record("game", TimerEvent::Start, self.game_timer.get_elapsed());
+                    self.signal_buzzer(BuzzerSignal::Start);
+                }
+                if held.left_prep {
+                    if let Some(ref mut prep) = self.left_prep {
+                        prep.start();
+                    }
+                }
+                if held.right_prep {
+                    if let Some(ref mut prep) = self.right_prep {
+                        prep.start();
+                    }
+                }
+                if held.shot_clock {
+                    if let Some(ref mut clock) = self.shot_clock {
+                        clock.timer.start();
+                    }
+                }
+                if held.left {
+                    self.left_timer.resume();
+                    self.record("left", TimerEvent::Start, self.left_timer.get_elapsed());
+                }
+                if held.right {
+                    self.right_timer.resume();
+                    self.record("right", TimerEvent::Start, self.right_timer.get_elapsed());
+                }
+                self.audio_controller.resume();
+            }
+            None => {
+                self.held = Some(HeldTimers {
+                    game: self.game_timer.is_running(),
+                    left: self.left_timer.is_running(),
+                    right: self.right_timer.is_running(),
+                    left_prep: self.left_prep.as_ref().is_some_and(Timer::is_running),
+                    right_prep: self.right_prep.as_ref().is_some_and(Timer::is_running),
+                    shot_clock: self
+                        .shot_clock
+                        .as_ref()
+                        .is_some_and(|clock| clock.timer.is_running()),
+                });
+                if let Some(ref mut clock) = self.shot_clock {
+                    clock.timer.stop();
+                }
+                if let Some(ref mut prep) = self.left_prep {
+                    prep.stop();
+                }
+                if let Some(ref mut prep) = self.right_prep {
+                    prep.stop();
+                }
+                if self.game_timer.is_running() {
+                    self.signal_buzzer(BuzzerSignal::Stop);
+                }
+                self.stop_game_timer();
+                self.stop_side(Side::Left);
+                self.stop_side(Side::Right);
+                self.audio_controller.pause();
+                self.sound_horn(|horn| horn.hold_blasts);
+            }
+        }
+    }
+
+    /// Ends the end early, or as the game timer runs out: moves a sequence on, or stops
+    /// everything and sounds the end signals
+    pub fn freeze(&mut self) {
+        if self.is_held() {
+            return;
+        }
+        match self.phase {
+            SequencePhase::Idle => {}
+            // The end is already over, and its signals have sounded
+            SequencePhase::EndOfEnd => return,
+            SequencePhase::Break => {
+                self.start_next_end();
+                return;
+            }
+            _ => {
+                self.finish_line();
+                return;
+            }
+        }
+        self.interval = None;
+        self.stop_game_timer();
+        self.stop_side(Side::Left);
+        self.stop_side(Side::Right);
+        self.left_prep = None;
+        self.right_prep = None;
+        if let Some(ref game_stop_file) = self.config.game_timer.end_sfx {
+            self.audio_controller.play_file(game_stop_file);
+        } else {
+            self.audio_controller.stop();
+        }
+        self.sound_horn(|horn| horn.end_blasts);
+        self.signal_buzzer(BuzzerSignal::Stop);
+    }
+}
+
+fn parse_wall_time(time: &str) -> Option<chrono::NaiveTime> {
+    let time = time.trim();
+    return chrono::NaiveTime::parse_from_str(time, "%H:%M:%S")
+        .or_else(|_| chrono::NaiveTime::parse_from_str(time, "%H:%M"))
+        .ok();
+}
+
+/// Today at `time`, or tomorrow if that has already passed
+fn next_occurrence(time: chrono::NaiveTime) -> chrono::NaiveDateTime {
+    let now = chrono::Local::now().naive_local();
+    let mut start = now.date().and_time(time);
+    if start <= now {
+        start += chrono::Duration::days(1);
+    }
+    return start;
+}
+
+fn show_schedule_dialog(window: &gtk::ApplicationWindow, timers: Arc<Mutex<ApplicationState>>) {
+    let dialog = gtk::Dialog::with_buttons(
+        Some("Schedule start"),
+        Some(window),
+        gtk::DialogFlags::MODAL | gtk::DialogFlags::DESTROY_WITH_PARENT,
+        &[
+            ("Cancel", gtk::ResponseType::Cancel),
+            ("Schedule", gtk::ResponseType::Accept),
+        ],
+    );
+    dialog.set_default_response(gtk::ResponseType::Accept);
+    let entry = gtk::Entry::new();
+    entry.set_placeholder_text(Some("HH:MM:SS (empty to cancel)"));
+    entry.set_activates_default(true);
+    dialog.content_area().pack_start(&entry, true, true, 6);
+    dialog.connect_response(move |dialog, response| {
+        if response == gtk::ResponseType::Accept {
+            let text = entry.text();
+            let mut timers = timers.lock().unwrap();
+            if text.trim().is_empty() {
+                timers.cancel_scheduled_start();
+            } else {
+                match parse_wall_time(&text) {
+                    Some(time) => timers.schedule_start(time),
+                    None => log::warn!("Invalid start time {text:?}"),
+                }
+            }
+        }
+        dialog.close();
+    });
+    dialog.show_all();
+}
+
+fn activate(application: &gtk::Application, timers: Arc<Mutex<ApplicationState>>, windowed: bool) {
+    let (mode, windowed) = {
+        let state = timers.lock().unwrap();
+        (state.config.monitors, windowed || state.config.windowed)
+    };
+    let monitors = match mode {
+        MonitorMode::Single => 1,
+        _ => gdk::Display::default().map_or(1, |display| display.n_monitors().max(1)),
+    };
+    let windows: Vec<(gtk::ApplicationWindow, gtk::Window)> = (0..monitors)
+        .map(|monitor| {
+            // Splitting needs a second screen to put the right side on
+            let part = match (mode, monitor) {
+                (MonitorMode::Split, 0) if monitors >= 2 => ScreenPart::Left,
+                (MonitorMode::Split, 1) => ScreenPart::Right,
+                _ => ScreenPart::All,
+            };
+            return build_window(
+                application,
+                Arc::clone(&timers),
+                windowed,
+                (monitors > 1).then_some(monitor),
+                part,
+            );
+        })
+        .collect();
+
+    // Get ready for activation
+    application.connect_activate(move |_| {
+        for (window, root) in &windows {
+            window.show_all();
+            // A rotated display is laid out in a window of its own
+            root.show_all();
+        }
+    });
+}
+
+/// Makes the window fullscreen, on its own monitor if it was given one
+fn fullscreen_on(window: &gtk::ApplicationWindow, monitor: Option<i32>) {
+    match (monitor, gdk::Screen::default()) {
+        (Some(monitor), Some(screen)) => window.fullscreen_on_monitor(&screen, monitor),
+        _ => window.fullscreen(),
+    }
+}
+
+/// Builds a window showing the timers, on the given monitor if there's more than one, along
+/// with the toplevel the display is mounted in. The first window built is the primary one,
+/// which advances the shared state and watches the config.
+fn build_window(
+    application: &gtk::Application,
+    timers: Arc<Mutex<ApplicationState>>,
+    windowed: bool,
+    monitor: Option<i32>,
+    part: ScreenPart,
+) -> (gtk::ApplicationWindow, gtk::Window) {
+    let primary = monitor.unwrap_or(0) == 0;
+    let state = timers.lock().unwrap();
+
+    // Set up the window
+    let window = gtk::ApplicationWindow::new(application);
+    window.style_context().add_class("archery-timer");
+    if let Some(geometry) = monitor
+        .and_then(|monitor| gdk::Display::default()?.monitor(monitor))
+        .map(|monitor| monitor.geometry())
+    {
+        window.move_(geometry.x(), geometry.y());
+    }
+    if !windowed {
+        fullscreen_on(&window, monitor);
+    }
+
+    // Create basic structure within window
+    let overlay = gtk::Overlay::new();
+    let vertical = state.config.layout == Layout::Vertical;
+    let color_transition = Duration::from_millis(state.config.color_transition_ms);
+    let bar = gtk::Box::new(
+        match vertical {
+            true => gtk::Orientation::Vertical,
+            false => gtk::Orientation::Horizontal,
+        },
+        0,
+    );
+    window.set_events(EventMask::KEY_PRESS_MASK);
+    hide_cursor(&window, state.config.cursor_reveal_secs);
+    let root = rotation::mount(&window, &overlay, state.config.rotation);
+    root.style_context().add_class("archery-timer");
+    let layout = gtk::Box::new(gtk::Orientation::Vertical, 0);
+    overlay.add(&layout);
+    layout.pack_start(&bar, true, true, 0);
+
+    let ticker = Ticker::new();
+    layout.pack_end(ticker.widget(), false, false, 0);
+
+    let stats_label = gtk::Label::new(None);
+    stats_label.style_context().add_class("stats");
+    stats_label.set_no_show_all(true);
+    layout.pack_end(&stats_label, false, false, 0);
+
+    let reset_progress = gtk::ProgressBar::new();
+    reset_progress.style_context().add_class("reset-progress");
+    reset_progress.set_text(Some("Hold to reset"));
+    reset_progress.set_show_text(true);
+    reset_progress.set_valign(gtk::Align::Center);
+    reset_progress.set_margin_start(100);
+    reset_progress.set_margin_end(100);
+    reset_progress.set_no_show_all(true);
+
+    // Covers the timers, under the banners
+    let wall_clock = gtk::Label::new(None);
+    wall_clock.style_context().add_class("wall-clock");
+    wall_clock.set_no_show_all(true);
+    overlay.add_overlay(&wall_clock);
+
+    overlay.add_overlay(&reset_progress);
+
+    let hold_banner = gtk::Label::new(Some("HOLD"));
+    hold_banner.style_context().add_class("hold-banner");
+    hold_banner.set_halign(gtk::Align::Center);
+    hold_banner.set_valign(gtk::Align::Center);
+    hold_banner.set_no_show_all(true);
+    overlay.add_overlay(&hold_banner);
+
+    let schedule_banner = gtk::Label::new(None);
+    schedule_banner.style_context().add_class("schedule-banner");
+    schedule_banner.set_halign(gtk::Align::Center);
+    schedule_banner.set_valign(gtk::Align::Center);
+    schedule_banner.set_no_show_all(true);
+    overlay.add_overlay(&schedule_banner);
+
+    let adjust_banner = gtk::Label::new(None);
+    adjust_banner.style_context().add_class("schedule-banner");
+    adjust_banner.set_halign(gtk::Align::Center);
+    adjust_banner.set_valign(gtk::Align::Center);
+    adjust_banner.set_no_show_all(true);
+    overlay.add_overlay(&adjust_banner);
+
+    let gpio_banner = gtk::Label::new(None);
+    gpio_banner.style_context().add_class("gpio-error");
+    gpio_banner.set_halign(gtk::Align::Start);
+    gpio_banner.set_valign(gtk::Align::End);
+    gpio_banner.set_no_show_all(true);
+    overlay.add_overlay(&gpio_banner);
+
+    let toast = gtk::Label::new(None);
+    toast.style_context().add_class("toast");
+    toast.set_halign(gtk::Align::Center);
+    toast.set_valign(gtk::Align::End);
+    toast.set_no_show_all(true);
+    overlay.add_overlay(&toast);
+
+    let summary_banner = gtk::Label::new(None);
+    summary_banner.style_context().add_class("summary");
+    summary_banner.set_halign(gtk::Align::Center);
+    summary_banner.set_valign(gtk::Align::Center);
+    summary_banner.set_no_show_all(true);
+    overlay.add_overlay(&summary_banner);
+
+    let left = gtk::Box::new(gtk::Orientation::Vertical, 0);
+    bar.pack_start(&left, true, true, 0);
+    left.style_context().add_class("left-timer");
+    let left_panel = PanelStyle::new(
+        &left,
+        &state.config.left_timer.color,
+        &state.config.left_timer.text_color,
+        color_transition,
+    );
+
+    let left_label = gtk::Label::new(Some("Test left"));
+    left_label.style_context().add_class("clock");
+    let left_splits = gtk::Label::new(None);
+    left_splits.style_context().add_class("splits");
+    let left_elapsed = gtk::Label::new(None);
+    left_elapsed.style_context().add_class("elapsed");
+    left_elapsed.set_no_show_all(true);
+    let left_score = gtk::Label::new(None);
+    left_score.style_context().add_class("score");
+    left_score.set_no_show_all(true);
+    let left_name = gtk::Label::new(None);
+    left_name.style_context().add_class("archer-name");
+    left_name.set_no_show_all(true);
+    let left_target = gtk::Label::new(state.target_name(Side::Left).as_deref());
+    left_target.style_context().add_class("target-label");
+    left_target.set_no_show_all(left_target.text().is_empty());
+    let left_caption = gtk::Label::new(state.config.left_timer.label.as_deref());
+    left_caption.style_context().add_class("timer-label");
+    left_caption.set_no_show_all(left_caption.text().is_empty());
+    let left_caption_above = state.config.left_timer.label_position == LabelPosition::Above;
+    if state.config.left_timer.flipped {
+        left_label.set_angle(180.0);
+        left_caption.set_angle(180.0);
+        left_splits.set_angle(180.0);
+        left_elapsed.set_angle(180.0);
+        left_score.set_angle(180.0);
+        left_name.set_angle(180.0);
+        left_target.set_angle(180.0);
+        left.pack_start(&left_splits, false, false, 3);
+        left.pack_end(&left_target, false, false, 3);
+        left.pack_end(&left_name, false, false, 3);
+        left.pack_end(&left_score, false, false, 3);
+        if left_caption_above {
+            left.pack_end(&left_caption, false, false, 3);
+        } else {
+            left.pack_start(&left_caption, false, false, 3);
+        }
+        // Beneath the clock once turned over
+        left.pack_start(&left_elapsed, false, false, 3);
+    } else {
+        left.pack_start(&left_target, false, false, 3);
+        left.pack_start(&left_name, false, false, 3);
+        left.pack_start(&left_score, false, false, 3);
+        left.pack_end(&left_splits, false, false, 3);
+        if left_caption_above {
+            left.pack_start(&left_caption, false, false, 3);
+        } else {
+            left.pack_end(&left_caption, false, false, 3);
+        }
+    }
+    left.pack_start(&left_label, true, true, 3);
+    if !state.config.left_timer.flipped {
+        left.pack_start(&left_elapsed, false, false, 3);
+    }
+
+    let center = gtk::Box::new(gtk::Orientation::Horizontal, 0);
+    bar.pack_start(&center, false, false, 0);
+    center.style_context().add_class("center-timer");
+    let center_panel = PanelStyle::new(
+        &center,
+        &state.config.game_timer.color,
+        &state.config.game_timer.text_color,
+        color_transition,
+    );
+    let center_label = gtk::Label::new(Some("Test center"));
+    center_label.style_context().add_class("clock");
+    // Runs along the strip between the timers
+    let center_angle = match vertical {
+        true => 0.0,
+        false => 90.0,
+    };
+    center_label.set_angle(center_angle);
+    center.pack_start(&center_label, true, true, 3);
+    let line_label = gtk::Label::new(None);
+    line_label.set_angle(center_angle);
+    line_label.style_context().add_class("line-label");
+    center.pack_start(&line_label, false, false, 3);
+    let info_panel = gtk::Box::new(gtk::Orientation::Vertical, 0);
+    info_panel.style_context().add_class("info-panel");
+    info_panel.set_valign(gtk::Align::Center);
+    let info_end = gtk::Label::new(None);
+    let info_line = gtk::Label::new(None);
+    let info_phase = gtk::Label::new(None);
+    for label in [&info_end, &info_line, &info_phase] {
+        label.style_context().add_class("info");
+        info_panel.pack_start(label, false, false, 3);
+    }
+    info_line.set_no_show_all(true);
+    info_phase.set_no_show_all(true);
+    center.pack_start(&info_panel, false, false, 3);
+    if let Some(ref logo) = state.config.logo {
+        match gtk::gdk_pixbuf::Pixbuf::from_file_at_scale(&logo.path, -1, logo.height, true) {
+            Ok(pixbuf) => {
+                let image = gtk::Image::from_pixbuf(Some(&pixbuf));
+                image.style_context().add_class("logo");
+                image.set_margin(logo.margin);
+                let (halign, valign) = match logo.position {
+                    LogoPosition::TopLeft => (gtk::Align::Start, gtk::Align::Start),
+                    LogoPosition::TopRight => (gtk::Align::End, gtk::Align::Start),
+                    LogoPosition::BottomLeft => (gtk::Align::Start, gtk::Align::End),
+                    LogoPosition::BottomRight => (gtk::Align::End, gtk::Align::End),
+                    LogoPosition::Center => (gtk::Align::Center, gtk::Align::Center),
+                };
+                image.set_halign(halign);
+                image.set_valign(valign);
+                match logo.position {
+                    LogoPosition::Center => center.pack_start(&image, false, false, 3),
+                    _ => overlay.add_overlay(&image),
+                }
+            }
+            Err(err) => log::error!("Failed to load logo {:?}: {err}", logo.path),
+        }
+    }
+    match state.config.info_panel {
+        true => line_label.set_no_show_all(true),
+        false => info_panel.set_no_show_all(true),
+    }
+
+    let right = gtk::Box::new(gtk::Orientation::Vertical, 0);
+    bar.pack_end(&right, true, true, 0);
+    right.style_context().add_class("right-timer");
+    let right_panel = PanelStyle::new(
+        &right,
+        &state.config.right_timer.color,
+        &state.config.right_timer.text_color,
+        color_transition,
+    );
+
+    let right_label = gtk::Label::new(Some("Test right"));
+    right_label.style_context().add_class("clock");
+    let right_splits = gtk::Label::new(None);
+    right_splits.style_context().add_class("splits");
+    let right_elapsed = gtk::Label::new(None);
+    right_elapsed.style_context().add_class("elapsed");
+    right_elapsed.set_no_show_all(true);
+    let right_score = gtk::Label::new(None);
+    right_score.style_context().add_class("score");
+    right_score.set_no_show_all(true);
+    let right_name = gtk::Label::new(None);
+    right_name.style_context().add_class("archer-name");
+    right_name.set_no_show_all(true);
+    let right_target = gtk::Label::new(state.target_name(Side::Right).as_deref());
+    right_target.style_context().add_class("target-label");
+    right_target.set_no_show_all(right_target.text().is_empty());
+    let right_caption = gtk::Label::new(state.config.right_timer.label.as_deref());
+    right_caption.style_context().add_class("timer-label");
+    right_caption.set_no_show_all(right_caption.text().is_empty());
+    FontStyle::attach(
+        &root,
+        &[&left_label, &center_label, &right_label],
+        state.config.font.clone(),
+    );
+    let right_caption_above = state.config.right_timer.label_position == LabelPosition::Above;
+    if state.config.right_timer.flipped {
+        right_label.set_angle(180.0);
+        right_caption.set_angle(180.0);
+        right_splits.set_angle(180.0);
+        right_elapsed.set_angle(180.0);
+        right_score.set_angle(180.0);
+        right_name.set_angle(180.0);
+        right_target.set_angle(180.0);
+        right.pack_start(&right_splits, false, false, 3);
+        right.pack_end(&right_target, false, false, 3);
+        right.pack_end(&right_name, false, false, 3);
+        right.pack_end(&right_score, false, false, 3);
+        if right_caption_above {
+            right.pack_end(&right_caption, false, false, 3);
+        } else {
+            right.pack_start(&right_caption, false, false, 3);
+        }
+        // Beneath the clock once turned over
+        right.pack_start(&right_elapsed, false, false, 3);
+    } else {
+        right.pack_start(&right_target, false, false, 3);
+        right.pack_start(&right_name, false, false, 3);
+        right.pack_start(&right_score, false, false, 3);
+        right.pack_end(&right_splits, false, false, 3);
+        if right_caption_above {
+            right.pack_start(&right_caption, false, false, 3);
+        } else {
+            right.pack_end(&right_caption, false, false, 3);
+        }
+    }
+    right.pack_start(&right_label, true, true, 3);
+    if !state.config.right_timer.flipped {
+        right.pack_start(&right_elapsed, false, false, 3);
+    }
+
+    match part {
+        ScreenPart::All => {}
+        ScreenPart::Left => right.set_no_show_all(true),
+        ScreenPart::Right => left.set_no_show_all(true),
+    }
+
+    drop(state);
+    let config_monitors = ConfigMonitors::default();
+    if primary {
+        watch_config(Arc::clone(&timers), &config_monitors);
+    }
+
+    {
+        let state = Arc::clone(&timers);
+        window.connect_key_press_event(move |window, key| {
+            let key = key.keyval();
+            let action = state.lock().unwrap().keymap.action(key);
+            let Some(action) = action else {
+                if let Some(arrow) = key.to_unicode().and_then(arrow_for_char) {
+                    let mut state = state.lock().unwrap();
+                    if state.is_scoring() {
+                        state.score_arrow(arrow);
+                        return glib::Propagation::Stop;
+                    }
+                }
+                return glib::Propagation::Proceed;
+            };
+            match action {
+                Action::Reset => {
+                    let mut state = state.lock().unwrap();
+                    state.reset();
+                    return glib::Propagation::Stop;
+                }
+                Action::CycleProfile => {
+                    let mut state = state.lock().unwrap();
+                    state.cycle_profile();
+                    return glib::Propagation::Stop;
+                }
+                Action::SaveReport => {
+                    let state = state.lock().unwrap();
+                    state.save_report();
+                    return glib::Propagation::Stop;
+                }
+                Action::DismissSummary => {
+                    let mut state = state.lock().unwrap();
+                    state.dismiss_summary();
+                    return glib::Propagation::Stop;
+                }
+                Action::NextEnd => {
+                    let mut state = state.lock().unwrap();
+                    state.advance_end();
+                    return glib::Propagation::Stop;
+                }
+                Action::PreviousEnd => {
+                    let mut state = state.lock().unwrap();
+                    state.rewind_end();
+                    return glib::Propagation::Stop;
+                }
+                Action::Game => {
+                    let mut state = state.lock().unwrap();
+                    if state.game_timer.is_running() {
+                        state.freeze();
+                    } else {
+                        state.start_game_timer();
+                    }
+                    return glib::Propagation::Stop;
+                }
+                Action::ExtraTime => {
+                    let mut state = state.lock().unwrap();
+                    state.grant_extra_time();
+                    return glib::Propagation::Stop;
+                }
+                Action::CycleLeftPreset => {
+                    let mut state = state.lock().unwrap();
+                    state.cycle_preset(Side::Left);
+                    return glib::Propagation::Stop;
+                }
+                Action::CycleRightPreset => {
+                    let mut state = state.lock().unwrap();
+                    state.cycle_preset(Side::Right);
+                    return glib::Propagation::Stop;
+                }
+                Action::ToggleStats => {
+                    let mut state = state.lock().unwrap();
+                    state.toggle_stats();
+                    return glib::Propagation::Stop;
+                }
+                Action::VolumeUp => {
+                    state.lock().unwrap().change_volume(1);
+                    return glib::Propagation::Stop;
+                }
+                Action::VolumeDown => {
+                    state.lock().unwrap().change_volume(-1);
+                    return glib::Propagation::Stop;
+                }
+                Action::ToggleMetronome => {
+                    state.lock().unwrap().toggle_metronome();
+                    return glib::Propagation::Stop;
+                }
+                Action::ToggleMusic => {
+                    state.lock().unwrap().toggle_music();
+                    return glib::Propagation::Stop;
+                }
+                Action::SkipTrack => {
+                    state.lock().unwrap().skip_track();
+                    return glib::Propagation::Stop;
+                }
+                Action::ToggleFullscreen => {
+                    let fullscreen = window.window().is_some_and(|gdk_window| {
+                        gdk_window.state().contains(gdk::WindowState::FULLSCREEN)
+                    });
+                    if fullscreen {
+                        window.unfullscreen();
+                    } else {
+                        fullscreen_on(window, monitor);
+                    }
+                    return glib::Propagation::Stop;
+                }
+                Action::Split => {
+                    let mut state = state.lock().unwrap();
+                    state.split();
+                    return glib::Propagation::Stop;
+                }
+                Action::Schedule => {
+                    show_schedule_dialog(window, Arc::clone(&state));
+                    return glib::Propagation::Stop;
+                }
+                Action::SaveChanges => {
+                    settings::show_save_changes_dialog(window, Arc::clone(&state));
+                    return glib::Propagation::Stop;
+                }
+                Action::Settings => {
+                    settings::show_settings_dialog(window, Arc::clone(&state));
+                    return glib::Propagation::Stop;
+                }
+                Action::CycleRound => {
+                    let mut state = state.lock().unwrap();
+                    state.cycle_round();
+                    return glib::Propagation::Stop;
+                }
+                Action::Hold => {
+                    let mut state = state.lock().unwrap();
+                    state.toggle_hold();
+                    return glib::Propagation::Stop;
+                }
+                Action::StartLeft => {
+                    let mut state = state.lock().unwrap();
+                    state.start_left_timer();
+                    return glib::Propagation::Stop;
+                }
+                Action::StartRight => {
+                    let mut state = state.lock().unwrap();
+                    state.start_right_timer();
+                    return glib::Propagation::Stop;
+                }
+                Action::CycleLeftArcher => {
+                    let mut state = state.lock().unwrap();
+                    state.cycle_archer(Side::Left);
+                    return glib::Propagation::Stop;
+                }
+                Action::CycleRightArcher => {
+                    let mut state = state.lock().unwrap();
+                    state.cycle_archer(Side::Right);
+                    return glib::Propagation::Stop;
+                }
+                Action::SwitchScoringSide => {
+                    let mut state = state.lock().unwrap();
+                    state.switch_scoring_side();
+                    return glib::Propagation::Stop;
+                }
+                Action::UndoArrow => {
+                    let mut state = state.lock().unwrap();
+                    state.undo_arrow();
+                    return glib::Propagation::Stop;
+                }
+            }
+        });
+    }
+
+    let refresh_interval = Duration::from_millis(timers.lock().unwrap().config.refresh_interval_ms);
+    let display = Rc::new(TimerDisplay {
+        timers,
+        primary,
+        left_label,
+        center_label,
+        right_label,
+        line_label,
+        info_end,
+        info_line,
+        info_phase,
+        left_splits,
+        left_elapsed,
+        right_splits,
+        right_elapsed,
+        left_score,
+        right_score,
+        left_name,
+        right_name,
+        hold_banner,
+        schedule_banner,
+        adjust_banner,
+        gpio_banner,
+        toast,
+        wall_clock,
+        ticker,
+        reset_progress,
+        summary_banner,
+        stats_label,
+        left_panel,
+        center_panel,
+        right_panel,
+        _config_monitors: config_monitors,
+    });
+    schedule_refresh(display, refresh_interval);
+    return (window, root);
+}
+
+// How long passing problems stay on screen
+const TOAST_DURATION: Duration = Duration::from_secs(8);
+
+// Used while any countdown is showing tenths so the last digit actually moves smoothly, and
+// while panels fade between colors
+const FAST_REFRESH_INTERVAL: Duration = Duration::from_millis(25);
+
+/// Widgets updated by the refresh loop
+struct TimerDisplay {
+    timers: Arc<Mutex<ApplicationState>>,
+    // Only one window advances the state, however many show it
+    primary: bool,
+    left_label: gtk::Label,
+    center_label: gtk::Label,
+    right_label: gtk::Label,
+    line_label: gtk::Label,
+    info_end: gtk::Label,
+    info_line: gtk::Label,
+    info_phase: gtk::Label,
+    left_splits: gtk::Label,
+    left_elapsed: gtk::Label,
+    right_splits: gtk::Label,
+    right_elapsed: gtk::Label,
+    left_score: gtk::Label,
+    right_score: gtk::Label,
+    left_name: gtk::Label,
+    right_name: gtk::Label,
+    hold_banner: gtk::Label,
+    schedule_banner: gtk::Label,
+    adjust_banner: gtk::Label,
+    gpio_banner: gtk::Label,
+    toast: gtk::Label,
+    wall_clock: gtk::Label,
+    ticker: Ticker,
+    reset_progress: gtk::ProgressBar,
+    summary_banner: gtk::Label,
+    stats_label: gtk::Label,
+    left_panel: PanelStyle,
+    center_panel: PanelStyle,
+    right_panel: PanelStyle,
+    // Kept alive so config edits keep being picked up. Empty for the other windows.
+    _config_monitors: ConfigMonitors,
+}
+impl TimerDisplay {
+    /// Updates all widgets, returning how long to wait before the next refresh. The current
+    /// interval is kept if the state is busy.
+    pub fn refresh(&self, mut interval: Duration) -> Duration {
+        if let Ok(mut timers) = self.timers.try_lock() {
+            interval = Duration::from_millis(timers.config.refresh_interval_ms);
+            if self.primary {
+                timers.tick();
+            }
+            let game_flash = timers.game_flash();
+            let (color, text_color) =
+                timers.themed_colors(timers.center_color(), &timers.config.game_timer.text_color);
+            let fade = !timers.game_flashes_background();
+            match game_flash {
+                Some(FlashTarget::Background) => {
+                    self.center_panel.set_colors(&text_color, &color, fade);
+                }
+                _ => self.center_panel.set_colors(&color, &text_color, fade),
+            }
+            let left_flash = timers.side_flash(Side::Left);
+            let right_flash = timers.side_flash(Side::Right);
+            let (color, text_color) =
+                timers.themed_colors(timers.left_color(), &timers.config.left_timer.text_color);
+            let fade = !timers.side_flashes_background(Side::Left);
+            match left_flash {
+                Some(FlashTarget::Background) => {
+                    self.left_panel.set_colors(&text_color, &color, fade);
+                }
+                _ => self.left_panel.set_colors(&color, &text_color, fade),
+            }
+            let (color, text_color) =
+                timers.themed_colors(timers.right_color(), &timers.config.right_timer.text_color);
+            let fade = !timers.side_flashes_background(Side::Right);
+            match right_flash {
+                Some(FlashTarget::Background) => {
+                    self.right_panel.set_colors(&text_color, &color, fade);
+                }
+                _ => self.right_panel.set_colors(&color, &text_color, fade),
+            }
+            let mut fading = false;
+            for panel in [&self.center_panel, &self.left_panel, &self.right_panel] {
+                fading |= panel.step();
+            }
+            if fading {
+                interval = interval.min(FAST_REFRESH_INTERVAL);
+            }
+            self.left_panel.set_phase(timers.side_phase(Side::Left));
+            self.right_panel.set_phase(timers.side_phase(Side::Right));
+            let shooting = |side| {
+                return matches!(
+                    timers.side_phase(side),
+                    SidePhase::Shooting | SidePhase::Warning
+                );
+            };
+            let active = match (shooting(Side::Left), shooting(Side::Right)) {
+                _ if !timers.config.highlight_active => None,
+                (true, false) => Some(Side::Left),
+                (false, true) => Some(Side::Right),
+                _ => None,
+            };
+            for (side, panel) in [
+                (Side::Left, &self.left_panel),
+                (Side::Right, &self.right_panel),
+            ] {
+                set_style_class(&panel.widget, "active", active == Some(side));
+                set_style_class(
+                    &panel.widget,
+                    "inactive",
+                    active.is_some_and(|active| active != side),
+                );
+            }
+            let center_caption = timers.center_caption().unwrap_or_default();
+            let info_end = timers.end_caption();
+            let info_line = timers.current_line().map(str::to_owned);
+            let info_phase = timers.phase.name();
+            let game_duration = timers.game_timer.get_remaining().as_millis();
+            let left_duration = timers.side_display(Side::Left).as_millis();
+            let right_duration = timers.side_display(Side::Right).as_millis();
+            let left_overtime = timers.left_timer.get_overtime().as_millis();
+            let right_overtime = timers.right_timer.get_overtime().as_millis();
+
+            // Only values counting down switch to tenths
+            let tenths_threshold = timers
+                .config
+                .tenths_below_secs
+                .map(|secs| u128::from(secs) * 1000);
+            let show_tenths = |duration: u128, counts_down: bool| {
+                return counts_down
+                    && tenths_threshold.is_some_and(|threshold| duration < threshold);
+            };
+            let game_tenths = show_tenths(game_duration, true);
+            let left_tenths = left_overtime == 0
+                && show_tenths(left_duration, timers.side_counts_down(Side::Left));
+            let right_tenths = right_overtime == 0
+                && show_tenths(right_duration, timers.side_counts_down(Side::Right));
+            if (game_tenths && timers.game_timer.is_running())
+                || (left_tenths && timers.side_is_running(Side::Left))
+                || (right_tenths && timers.side_is_running(Side::Right))
+            {
+                interval = interval.min(FAST_REFRESH_INTERVAL);
+            }
+            let (left_splits, right_splits) = match timers.arrow_counts() {
+                Some((left_arrows, right_arrows)) => (
+                    format!("Arrow {}", left_arrows + 1),
+                    format!("Arrow {}", right_arrows + 1),
+                ),
+                None => (
+                    format_splits(
+                        &timers.left_splits,
+                        timers.preset_name(Side::Left),
+                        &timers.config.time_format,
+                    ),
+                    format_splits(
+                        &timers.right_splits,
+                        timers.preset_name(Side::Right),
+                        &timers.config.time_format,
+                    ),
+                ),
+            };
+            let time_format = timers.config.time_format.clone();
+            let stats = timers
+                .show_stats
+                .then(|| format_stats(&timers.session, &time_format));
+            let summary = timers
+                .summary()
+                .map(|session| format_summary(session, &time_format));
+            let left_name = timers.archer_name(Side::Left);
+            let right_name = timers.archer_name(Side::Right);
+            let left_elapsed = timers.side_elapsed(Side::Left);
+            let right_elapsed = timers.side_elapsed(Side::Right);
+            let scores = timers.scoreboard.as_ref().map(|scoreboard| {
+                let standings = scoreboard.set_standings();
+                return (
+                    format_score(scoreboard.side(Side::Left), standings, Side::Left),
+                    format_score(scoreboard.side(Side::Right), standings, Side::Right),
+                    scoreboard.entering(),
+                    standings.and_then(|standings| standings.winner),
+                );
+            });
+            let held = timers.is_held();
+            let until_start = timers.time_until_start();
+            let adjustment = timers.time_adjustment().map(|duration| {
+                format!("Time {}", time_format.format(duration.as_millis(), false))
+            });
+            let gpio_error = timers.gpio_error.clone();
+            let toast = timers.toast().map(str::to_owned);
+            let wall_clock = timers.wall_clock();
+            let ticker = timers.config.ticker.clone();
+            let reset_progress = timers.reset_hold_progress();
+            drop(timers);
+
+            self.hold_banner.set_visible(held);
+            match gpio_error {
+                Some(err) => {
+                    self.gpio_banner
+                        .set_text(&format!("GPIO unavailable: {err}"));
+                    self.gpio_banner.set_visible(true);
+                }
+                None => self.gpio_banner.set_visible(false),
+            }
+            match toast {
+                Some(toast) => {
+                    self.toast.set_text(&toast);
+                    self.toast.set_visible(true);
+                }
+                None => self.toast.set_visible(false),
+            }
+            if self.ticker.update(ticker.as_ref()) {
+                interval = interval.min(FAST_REFRESH_INTERVAL);
+            }
+            match reset_progress {
+                Some(progress) => {
+                    self.reset_progress.set_fraction(progress);
+                    self.reset_progress.set_visible(true);
+                    interval = interval.min(FAST_REFRESH_INTERVAL);
+                }
+                None => self.reset_progress.set_visible(false),
+            }
+            match wall_clock {
+                Some(wall_clock) => {
+                    self.wall_clock.set_text(&wall_clock);
+                    self.wall_clock.set_visible(true);
+                }
+                None => self.wall_clock.set_visible(false),
+            }
+            match adjustment {
+                Some(adjustment) => {
+                    self.adjust_banner.set_text(&adjustment);
+                    self.adjust_banner.set_visible(true);
+                }
+                None => self.adjust_banner.set_visible(false),
+            }
+            self.left_name
+                .set_text(left_name.as_deref().unwrap_or_default());
+            self.left_name.set_visible(left_name.is_some());
+            self.right_name
+                .set_text(right_name.as_deref().unwrap_or_default());
+            self.right_name.set_visible(right_name.is_some());
+            match scores {
+                Some((left_score, right_score, entering, winner)) => {
+                    self.left_score.set_text(&left_score);
+                    self.right_score.set_text(&right_score);
+                    set_style_class(
+                        &self.left_score,
+                        "entering",
+                        winner.is_none() && entering == Side::Left,
+                    );
+                    set_style_class(
+                        &self.right_score,
+                        "entering",
+                        winner.is_none() && entering == Side::Right,
+                    );
+                    set_style_class(&self.left_score, "winner", winner == Some(Side::Left));
+                    set_style_class(&self.right_score, "winner", winner == Some(Side::Right));
+                    self.left_score.set_visible(true);
+                    self.right_score.set_visible(true);
+                }
+                None => {
+                    self.left_score.set_visible(false);
+                    self.right_score.set_visible(false);
+                }
+            }
+            match summary {
+                Some(summary) => {
+                    self.summary_banner.set_text(&summary);
+                    self.summary_banner.set_visible(true);
+                }
+                None => self.summary_banner.set_visible(false),
+            }
+            match stats {
+                Some(stats) => {
+                    self.stats_label.set_text(&stats);
+                    self.stats_label.set_visible(true);
+                }
+                None => self.stats_label.set_visible(false),
+            }
+            match until_start {
+                Some(until_start) => {
+                    let secs = until_start.as_secs();
+                    let (h, m, s) = (secs / 3600, (secs / 60) % 60, secs % 60);
+                    self.schedule_banner.set_text(&if h > 0 {
+                        format!("Start in {h}:{m:02}:{s:02}")
+                    } else {
+                        format!("Start in {m:02}:{s:02}")
+                    });
+                    self.schedule_banner.set_visible(true);
+                }
+                None => self.schedule_banner.set_visible(false),
+            }
+            self.center_label
+                .set_text(&time_format.format(game_duration, game_tenths));
+            self.line_label.set_text(&center_caption);
+            self.info_end.set_text(&info_end);
+            self.info_line.set_text(&format!(
+                "Line {}",
+                info_line.as_deref().unwrap_or_default()
+            ));
+            self.info_line.set_visible(info_line.is_some());
+            self.info_phase.set_text(info_phase.unwrap_or_default());
+            self.info_phase.set_visible(info_phase.is_some());
+            self.left_splits.set_text(&left_splits);
+            self.right_splits.set_text(&right_splits);
+            set_elapsed_label(&self.left_elapsed, &time_format, left_elapsed);
+            set_elapsed_label(&self.right_elapsed, &time_format, right_elapsed);
+            set_side_label(
+                &self.left_label,
+                &time_format,
+                left_duration,
+                left_overtime,
+                left_tenths,
+            );
+            set_side_label(
+                &self.right_label,
+                &time_format,
+                right_duration,
+                right_overtime,
+                right_tenths,
+            );
+            set_style_class(
+                &self.center_label,
+                "flash",
+                game_flash == Some(FlashTarget::Digits),
+            );
+            set_style_class(
+                &self.left_label,
+                "flash",
+                left_flash == Some(FlashTarget::Digits),
+            );
+            set_style_class(
+                &self.right_label,
+                "flash",
+                right_flash == Some(FlashTarget::Digits),
+            );
+        }
+        return interval;
+    }
+}
+
+/// Background and text colors a panel fades between
+struct ColorFade {
+    from: (gdk::RGBA, gdk::RGBA),
+    to: (gdk::RGBA, gdk::RGBA),
+    started: std::time::Instant,
+}
+
+/// CSS provider holding a panel's colors, only reloaded when they actually change
+struct PanelStyle {
+    widget: gtk::Widget,
+    provider: gtk::CssProvider,
+    transition: Duration,
+    // Colors asked for, and those actually shown while fading towards them
+    colors: RefCell<(String, String)>,
+    shown: RefCell<Option<(gdk::RGBA, gdk::RGBA)>>,
+    fade: RefCell<Option<ColorFade>>,
+    phase: RefCell<Option<SidePhase>>,
+}
+impl PanelStyle {
+    pub fn new(
+        widget: &impl IsA<gtk::Widget>,
+        color: &str,
+        text_color: &str,
+        transition: Duration,
+    ) -> Self {
+        let panel = Self {
+            widget: widget.clone().upcast(),
+            provider: gtk::CssProvider::new(),
+            transition,
+            colors: RefCell::new((String::new(), String::new())),
+            shown: RefCell::new(None),
+            fade: RefCell::new(None),
+            phase: RefCell::new(None),
+        };
+        panel.set_colors(color, text_color, false);
+        widget.style_context().add_provider(&panel.provider, 100);
+        return panel;
+    }
+    /// Switches the panel to new colors, fading to them if `fade` and a transition is set
+    pub fn set_colors(&self, color: &str, text_color: &str, fade: bool) {
+        let mut colors = self.colors.borrow_mut();
+        if colors.0 == color && colors.1 == text_color {
+            return;
+        }
+        *colors = (color.to_owned(), text_color.to_owned());
+        let target = gdk::RGBA::parse(color)
+            .ok()
+            .zip(gdk::RGBA::parse(text_color).ok());
+        let from = *self.shown.borrow();
+        match (target, from) {
+            (Some(to), Some(from)) if fade && !self.transition.is_zero() => {
+                *self.fade.borrow_mut() = Some(ColorFade {
+                    from,
+                    to,
+                    started: std::time::Instant::now(),
+                });
+            }
+            _ => {
+                *self.fade.borrow_mut() = None;
+                *self.shown.borrow_mut() = target;
+                self.load(color, text_color);
+            }
+        }
+    }
+    /// Moves a fade along, returning whether it's still going
+    pub fn step(&self) -> bool {
+        let mut fade = self.fade.borrow_mut();
+        let Some(ref current) = *fade else {
+            return false;
+        };
+        let progress = current.started.elapsed().as_secs_f64() / self.transition.as_secs_f64();
+        let shown = match progress >= 1.0 {
+            true => current.to,
+            false => (
+                mix_colors(current.from.0, current.to.0, progress),
+                mix_colors(current.from.1, current.to.1, progress),
+            ),
+        };
+        *self.shown.borrow_mut() = Some(shown);
+        self.load(&shown.0.to_string(), &shown.1.to_string());
+        if progress >= 1.0 {
+            *fade = None;
+            return false;
+        }
+        return true;
+    }
+    fn load(&self, color: &str, text_color: &str) {
+        self.provider
+            .load_from_data(
+                format!("* {{ background-color: {color}; color: {text_color}; }}").as_bytes(),
+            )
+            .unwrap();
+    }
+    /// Gives the panel the style class of a side's phase
+    pub fn set_phase(&self, phase: SidePhase) {
+        let mut current = self.phase.borrow_mut();
+        if *current == Some(phase) {
+            return;
+        }
+        *current = Some(phase);
+        for other in SidePhase::ALL {
+            set_style_class(&self.widget, other.class(), other == phase);
+        }
+    }
+}
+
+/// Hides the pointer over the display once it's up. With `reveal_secs`, moving the mouse brings
+/// the pointer back until it's been still that long.
+fn hide_cursor(window: &gtk::ApplicationWindow, reveal_secs: Option<u64>) {
+    let set_blank = |window: &gtk::ApplicationWindow, blank: bool| {
+        let Some(gdk_window) = window.window() else {
+            return;
+        };
+        let cursor = match blank {
+            true => gdk::Cursor::for_display(&gdk_window.display(), gdk::CursorType::BlankCursor),
+            false => None,
+        };
+        gdk_window.set_cursor(cursor.as_ref());
+    };
+    window.connect_realize(move |window| set_blank(window, true));
+    let Some(reveal_secs) = reveal_secs else {
+        return;
+    };
+    window.add_events(EventMask::POINTER_MOTION_MASK);
+    let pending_hide: Rc<RefCell<Option<glib::SourceId>>> = Rc::new(RefCell::new(None));
+    window.connect_motion_notify_event(move |window, _| {
+        if let Some(source) = pending_hide.borrow_mut().take() {
+            source.remove();
+        } else {
+            set_blank(window, false);
+        }
+        let window = window.clone();
+        let pending = Rc::clone(&pending_hide);
+        *pending_hide.borrow_mut() = Some(glib::timeout_add_local_once(
+            Duration::from_secs(reveal_secs),
+            move || {
+                pending.borrow_mut().take();
+                set_blank(&window, true);
+            },
+        ));
+        return glib::Propagation::Proceed;
+    });
+}
+
+/// Color `amount` of the way from `from` to `to`
+fn mix_colors(from: gdk::RGBA, to: gdk::RGBA, amount: f64) -> gdk::RGBA {
+    let mix = |from: f64, to: f64| from + (to - from) * amount;
+    return gdk::RGBA::new(
+        mix(from.red(), to.red()),
+        mix(from.green(), to.green()),
+        mix(from.blue(), to.blue()),
+        mix(from.alpha(), to.alpha()),
+    );
+}
+
+fn set_style_class(widget: &impl IsA<gtk::Widget>, class: &str, enabled: bool) {
+    let style = widget.style_context();
+    if enabled {
+        style.add_class(class);
+    } else {
+        style.remove_class(class);
+    }
+}
+
+/// Shows how long a countdown has run for beneath it, or hides the readout
+fn set_elapsed_label(label: &gtk::Label, time_format: &TimeFormat, elapsed: Option<Duration>) {
+    match elapsed {
+        Some(elapsed) => {
+            label.set_text(&format!(
+                "Elapsed {}",
+                time_format.format(elapsed.as_millis(), false)
+            ));
+            label.set_visible(true);
+        }
+        None => label.set_visible(false),
+    }
+}
+
+/// Shows a side's time, switching to a flashing negative readout once it runs into overtime
+fn set_side_label(
+    label: &gtk::Label,
+    time_format: &TimeFormat,
+    duration_ms: u128,
+    overtime_ms: u128,
+    tenths: bool,
+) {
+    if overtime_ms > 0 {
+        label.set_text(&format!("-{}", time_format.format(overtime_ms, false)));
+        set_style_class(label, "overtime", overtime_ms % 1000 < 500);
+    } else {
+        label.set_text(&time_format.format(duration_ms, tenths));
+        set_style_class(label, "overtime", false);
+    }
+}
+
+/// Monitors for the config file and the files it includes
+type ConfigMonitors = Rc<RefCell<Vec<gio::FileMonitor>>>;
+
+/// Reloads the config whenever the file or anything it includes is saved, keeping the current
+/// one if the new one can't be read
+fn watch_config(timers: Arc<Mutex<ApplicationState>>, monitors: &ConfigMonitors) {
+    let files = timers.lock().unwrap().source.files();
+    let mut watching = Vec::new();
+    for path in files {
+        let monitor = match gio::File::for_path(&path)
+            .monitor_file(gio::FileMonitorFlags::NONE, gio::Cancellable::NONE)
+        {
+            Ok(monitor) => monitor,
+            Err(err) => {
+                log::warn!("Not watching {path:?} for changes: {err}");
+                continue;
+            }
+        };
+        let timers = Arc::clone(&timers);
+        let monitors = Rc::downgrade(monitors);
+        monitor.connect_changed(move |_, _, _, event| {
+            // Editors that save by replacing the file show up as a new file
+            if !matches!(
+                event,
+                gio::FileMonitorEvent::ChangesDoneHint | gio::FileMonitorEvent::Created
+            ) {
+                return;
+            }
+            timers.lock().unwrap().reload_source();
+            // The includes may have changed. The monitors are replaced once this one is done.
+            let timers = Arc::clone(&timers);
+            let monitors = monitors.clone();
+            glib::idle_add_local_once(move || {
+                if let Some(monitors) = monitors.upgrade() {
+                    watch_config(timers, &monitors);
+                }
+            });
+        });
+        watching.push(monitor);
+    }
+    *monitors.borrow_mut() = watching;
+}
+
+/// Runs the refresh loop, re-registering itself whenever the desired interval changes
+fn schedule_refresh(display: Rc<TimerDisplay>, interval: Duration) {
+    glib::timeout_add_local(interval, move || {
+        let next_interval = display.refresh(interval);
+        if next_interval != interval {
+            schedule_refresh(Rc::clone(&display), next_interval);
+            return glib::ControlFlow::Break;
+        }
+        return glib::ControlFlow::Continue;
+    });
+}
+
+fn format_stats(session: &Session, time_format: &TimeFormat) -> String {
+    let side_stats = |name: &str, stats: Option<SideStats>| {
+        return match stats {
+            Some(stats) => format!(
+                "{name}: avg {}  min {}  max {}",
+                time_format.format(stats.average.as_millis(), false),
+                time_format.format(stats.min.as_millis(), false),
+                time_format.format(stats.max.as_millis(), false),
+            ),
+            None => format!("{name}: -"),
+        };
+    };
+    return format!(
+        "{} ends     {}     {}",
+        session.ends().len(),
+        side_stats("Left", session.side_stats(Side::Left)),
+        side_stats("Right", session.side_stats(Side::Right)),
     );
-    // right_style.set_property("background-color", &state.config.right_timer.color);
+}
 
-    let right_label = gtk::Label::new(Some("Test right"));
-    if state.config.right_timer.flipped {
-        right_label.set_angle(180.0);
+/// Arrow value typed on the keyboard or numeric keypad. 0 scores a ten.
+fn arrow_for_char(key: char) -> Option<Arrow> {
+    return match key {
+        '1'..='9' => Some(Arrow::Ring(key as u8 - b'0')),
+        '0' => Some(Arrow::Ring(10)),
+        'x' | 'X' | '*' => Some(Arrow::X),
+        'm' | 'M' | '.' => Some(Arrow::Miss),
+        _ => None,
+    };
+}
+
+/// Running total (or set points in match play) followed by the arrows of the end being
+/// scored, or the last one completed
+fn format_score(score: &SideScore, standings: Option<SetStandings>, side: Side) -> String {
+    let arrows = match score.current() {
+        [] => score.ends().last().map(Vec::as_slice).unwrap_or_default(),
+        current => current,
+    };
+    let arrows = arrows
+        .iter()
+        .map(Arrow::to_string)
+        .collect::<Vec<_>>()
+        .join(" ");
+    return match standings {
+        Some(standings) if standings.winner == Some(side) => {
+            format!("Winner   {} sets", standings.side(side))
+        }
+        Some(standings) => format!("{} sets   {arrows}", standings.side(side)),
+        None => format!("{}   {arrows}", score.total()),
+    };
+}
+
+/// Table of each end's times followed by the session statistics
+fn format_summary(session: &Session, time_format: &TimeFormat) -> String {
+    let side_time = |duration: Option<Duration>| {
+        return match duration {
+            Some(duration) => time_format.format(duration.as_millis(), false),
+            None => "-".to_owned(),
+        };
+    };
+    let mut summary = String::from("End    Left    Right");
+    for end in session.ends() {
+        summary += &format!(
+            "\n{:>3}   {:>5}   {:>5}",
+            end.end,
+            side_time(end.left),
+            side_time(end.right),
+        );
     }
-    right.pack_start(&right_label, true, true, 3);
+    summary += "\n\n";
+    summary += &format_stats(session, time_format);
+    summary += "\n\nPress Esc to dismiss";
+    return summary;
+}
 
-    drop(state);
+const SPLITS_SHOWN: usize = 3;
 
-    {
-        let state = Arc::clone(&timers);
-        window.connect_key_press_event(move |_, key| {
-            let key = key.keyval();
-            match key {
-                gdk::keys::constants::r => {
-                    let mut state = state.lock().unwrap();
-                    state.clear_timers();
-                    return glib::Propagation::Stop;
-                }
-                gdk::keys::constants::g => {
-                    let mut state = state.lock().unwrap();
-                    if state.game_timer.is_running() {
-                        state.freeze();
-                    } else {
-                        state.start_game_timer();
-                    }
-                    return glib::Propagation::Stop;
-                }
-                gdk::keys::constants::j => {
-                    let mut state = state.lock().unwrap();
-                    state.start_left_timer();
-                    return glib::Propagation::Stop;
-                }
-                gdk::keys::constants::k => {
-                    let mut state = state.lock().unwrap();
-                    state.start_right_timer();
-                    return glib::Propagation::Stop;
-                }
-                _ => {
-                    return glib::Propagation::Proceed;
-                }
-            }
-        });
+/// Numbers the most recent splits, oldest first. Shows the side's time preset until the
+/// first split is taken.
+fn format_splits(splits: &[Duration], preset: Option<&str>, time_format: &TimeFormat) -> String {
+    if splits.is_empty() {
+        return preset.unwrap_or_default().to_owned();
     }
+    let first_shown = splits.len().saturating_sub(SPLITS_SHOWN);
+    return splits[first_shown..]
+        .iter()
+        .enumerate()
+        .map(|(i, split)| {
+            format!(
+                "{}: {}",
+                first_shown + i + 1,
+                time_format.format(split.as_millis(), false)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("   ");
+}
 
-    {
-        let window = window.clone();
-        glib::timeout_add_local(Duration::from_millis(100), move || {
-            if let Ok(mut timers) = timers.try_lock() {
-                let game_duration = timers.game_timer.get_remaining().as_millis();
-                let left_duration = timers.left_timer.get_duration().as_millis();
-                let right_duration = timers.right_timer.get_duration().as_millis();
-                if game_duration == 0
-                    && (timers.left_timer.is_running() || timers.right_timer.is_running())
-                {
-                    timers.freeze();
-                }
-                drop(timers);
-
-                center_label.set_text(&format_timestamp(game_duration));
-                left_label.set_text(&format_timestamp(left_duration));
-                right_label.set_text(&format_timestamp(right_duration));
-            }
-            if let (Some(gdk_window), Some(display)) = (window.window(), gdk::Display::default()) {
-                let cursor = gdk::Cursor::for_display(&display, gdk::CursorType::BlankCursor);
-                gdk_window.set_cursor(cursor.as_ref());
-            }
-            return glib::ControlFlow::Continue;
-        });
+#[derive(Parser, Debug)]
+#[command(version, about = "Timer display for archery ranges")]
+struct Args {
+    /// Config file to load, instead of searching the config directories
+    #[arg(long)]
+    config: Option<PathBuf>,
+    /// Run in a window instead of fullscreen
+    #[arg(long)]
+    windowed: bool,
+    /// Don't watch the GPIO buttons, e.g. on a development machine. Input devices and the
+    /// wireless receiver still work.
+    #[arg(long)]
+    no_gpio: bool,
+    /// Apply one of the config's named profiles
+    #[arg(long)]
+    profile: Option<String>,
+    /// Round template to start with
+    #[arg(long)]
+    round: Option<String>,
+    /// Write a commented default config to PATH (or stdout if omitted) and exit
+    #[arg(long, value_name = "PATH", num_args = 0..=1, default_missing_value = "-")]
+    init_config: Option<PathBuf>,
+}
+impl Args {
+    /// The config file to load, taking the profile into account
+    pub fn config_path(&self) -> PathBuf {
+        return self.config.clone().unwrap_or_else(find_config);
     }
+}
 
-    // Get ready for activation
-    application.connect_activate(move |_| {
-        window.show_all();
-    });
+const CONFIG_FILE_NAMES: [&str; 4] = ["config.yml", "config.yaml", "config.toml", "config.json"];
+
+/// Places the config is looked for, in order: the user's config directory, the system-wide
+/// one, then the working directory for running out of a checkout
+fn config_candidates() -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    let user_config_dir = match std::env::var_os("XDG_CONFIG_HOME") {
+        Some(dir) if !dir.is_empty() => Some(PathBuf::from(dir)),
+        _ => std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")),
+    };
+    if let Some(dir) = user_config_dir {
+        dirs.push(dir.join("archery-timer"));
+    }
+    dirs.push(PathBuf::from("/etc/archery-timer"));
+    dirs.push(PathBuf::from("."));
+    return dirs
+        .iter()
+        .flat_map(|dir| CONFIG_FILE_NAMES.iter().map(|name| dir.join(name)))
+        .collect();
+}
+
+/// Lists config problems in a dialog, returning whether to start anyway. Problems that stop
+/// the config loading at all can only be dismissed.
+fn show_config_problems(path: &Path, problems: &[String], can_continue: bool) -> bool {
+    for problem in problems {
+        log::error!("{path:?}: {problem}");
+    }
+    if gtk::init().is_err() {
+        return false;
+    }
+    let dialog = gtk::MessageDialog::new(
+        None::<&gtk::Window>,
+        gtk::DialogFlags::MODAL,
+        gtk::MessageType::Error,
+        gtk::ButtonsType::None,
+        &format!("Problems with the config at {}", path.display()),
+    );
+    dialog.set_secondary_text(Some(&problems.join("\n")));
+    dialog.add_button("Quit", gtk::ResponseType::Cancel);
+    if can_continue {
+        dialog.add_button("Start anyway", gtk::ResponseType::Accept);
+    }
+    let response = dialog.run();
+    dialog.close();
+    return response == gtk::ResponseType::Accept;
+}
+
+fn find_config() -> PathBuf {
+    let candidates = config_candidates();
+    return match candidates.iter().find(|candidate| candidate.is_file()) {
+        Some(config) => config.clone(),
+        None => {
+            log::error!("No config found, looked in {candidates:?}");
+            candidates[0].clone()
+        }
+    };
 }
 
-fn format_timestamp(timestamp_ms: u128) -> String {
-    let timestamp_s = timestamp_ms / 1000;
-    let s = timestamp_s % 60;
-    let timestamp_m = timestamp_s / 60;
-    let m = timestamp_m;
-    format!("{m:02}:{s:02}")
+const DEFAULT_CONFIG: &str = include_str!("../default-config.yml");
+
+/// Writes the default config for `--init-config`, refusing to replace an existing file
+fn init_config(path: &Path) -> std::io::Result<()> {
+    if path == Path::new("-") {
+        print!("{DEFAULT_CONFIG}");
+        return Ok(());
+    }
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let mut file = std::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(path)?;
+    return file.write_all(DEFAULT_CONFIG.as_bytes());
 }
 
 fn main() {
-    let config_file = std::fs::File::open("./config.yml").unwrap();
-    let config = serde_yaml::from_reader(config_file).unwrap();
-    let timers = Arc::new(Mutex::new(ApplicationState::new(config)));
+    let args = Args::parse();
+    if let Some(ref path) = args.init_config {
+        if let Err(err) = init_config(path) {
+            eprintln!("Failed to write {}: {err}", path.display());
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    simplelog::TermLogger::init(
+        log::LevelFilter::Info,
+        simplelog::Config::default(),
+        simplelog::TerminalMode::Stderr,
+        simplelog::ColorChoice::Auto,
+    )
+    .unwrap();
+
+    let config_path = args.config_path();
+    log::info!("Loading config from {config_path:?}");
+    let loaded = ConfigSource::load(config_path.clone(), args.profile.clone())
+        .and_then(|source| Ok((source.resolve()?, source)));
+    let (config, source) = match loaded {
+        Ok(loaded) => loaded,
+        Err(err) => {
+            show_config_problems(&config_path, &[err], false);
+            return;
+        }
+    };
+    let problems = validation::validate(&config);
+    if !problems.is_empty() && !show_config_problems(&config_path, &problems, true) {
+        return;
+    }
+    let mut state = ApplicationState::new(config, source);
+
+    if let Some(ref round) = args.round {
+        state.select_round_by_name(round);
+    }
+    state.restore_checkpoint();
+    let timers = Arc::new(Mutex::new(state));
 
     let application =
         gtk::Application::new(Some("com.shaunkeys.archery-timer"), Default::default());
@@ -422,30 +5512,52 @@ fn main() {
                 &provider,
                 gtk::STYLE_PROVIDER_PRIORITY_APPLICATION,
             );
+            if let Some(ref path) = timers.lock().unwrap().config.css {
+                let user_provider = gtk::CssProvider::new();
+                match user_provider.load_from_path(&path.to_string_lossy()) {
+                    // Just above the built-in styles so it can override them
+                    Ok(()) => gtk::StyleContext::add_provider_for_screen(
+                        &screen,
+                        &user_provider,
+                        gtk::STYLE_PROVIDER_PRIORITY_APPLICATION + 1,
+                    ),
+                    Err(err) => log::error!("Failed to load css {path:?}: {err}"),
+                }
+            }
 
-            activate(app, Arc::clone(&timers));
+            activate(app, Arc::clone(&timers), args.windowed);
         });
     }
 
-    if cfg!(feature = "gpio") {
+    {
         let timers = Arc::clone(&timers);
+        let use_gpio = cfg!(feature = "gpio") && !args.no_gpio;
         std::thread::spawn(move || {
             let runtime = tokio::runtime::Builder::new_current_thread()
                 .enable_all()
                 .build()
                 .unwrap();
-            runtime.block_on(track_gpio(Arc::clone(&timers)));
+            runtime.block_on(track_buttons(Arc::clone(&timers), use_gpio));
         });
     }
 
-    application.run();
+    // Arguments were already handled by clap, and GTK would reject them as unknown
+    let program: Vec<String> = std::env::args().take(1).collect();
+    application.run_with_args(&program);
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
 enum ButtonSide {
     Left,
     Right,
+    #[serde(rename = "game")]
     Internal,
+    Hold,
+    Reset,
+    Shutdown,
+    EncoderA,
+    EncoderB,
 }
 
 struct MaybeFuture<F: Future<Output = T> + Unpin, T>(Option<F>);
@@ -462,9 +5574,69 @@ impl<F: Future<Output = T> + Unpin, T> Future for MaybeFuture<F, T> {
     }
 }
 
+const HOLD_DEBOUNCE: Duration = Duration::from_millis(250);
+
 enum TimeoutEvent {
     TickTimeout,
     ResetTimeout,
+    ShutdownHeld,
+    DebounceEnded,
+}
+
+/// Per-button windows after an accepted edge in which further edges are put down to contact
+/// bounce. The level a line was last reported at inside a window is kept, and passed on once
+/// the window ends, so a button released during the bounce isn't left pressed.
+struct Debouncer {
+    windows: HashMap<ButtonSide, Duration>,
+    last_edge: HashMap<ButtonSide, Instant>,
+    settling: HashMap<ButtonSide, bool>,
+}
+impl Debouncer {
+    pub fn new(windows: HashMap<ButtonSide, Duration>) -> Self {
+        return Self {
+            windows,
+            last_edge: HashMap::new(),
+            settling: HashMap::new(),
+        };
+    }
+    fn window_end(&self, side: ButtonSide) -> Option<Instant> {
+        return Some(*self.last_edge.get(&side)? + *self.windows.get(&side)?);
+    }
+    /// Whether an edge falls inside its button's window, in which case its level is kept.
+    /// Outside the window, the edge replaces any level kept from before.
+    pub fn holds_back(&mut self, side: ButtonSide, level: bool, now: Instant) -> bool {
+        if self.window_end(side).is_some_and(|end| now < end) {
+            self.settling.insert(side, level);
+            return true;
+        }
+        self.settling.remove(&side);
+        return false;
+    }
+    /// Starts a button's window on an edge that was acted on
+    pub fn accepted(&mut self, side: ButtonSide, now: Instant) {
+        self.last_edge.insert(side, now);
+    }
+    /// When the first window with a level kept back ends
+    pub fn next_settle(&self) -> Option<Instant> {
+        return self
+            .settling
+            .keys()
+            .filter_map(|side| self.window_end(*side))
+            .min();
+    }
+    /// Takes the levels kept back by windows that have ended
+    pub fn settled(&mut self, now: Instant) -> Vec<(ButtonSide, bool)> {
+        let settled: Vec<(ButtonSide, bool)> = self
+            .settling
+            .iter()
+            .filter(|(side, _)| self.window_end(**side).is_none_or(|end| now >= end))
+            .map(|(side, level)| (*side, *level))
+            .collect();
+        for (side, _) in &settled {
+            self.settling.remove(side);
+        }
+        return settled;
+    }
 }
 
 struct ButtonTracker {
@@ -472,6 +5644,18 @@ struct ButtonTracker {
     left_state: bool,
     right_state: bool,
     internal_state: bool,
+    hold_state: bool,
+    reset_state: bool,
+    shutdown_state: bool,
+    encoder_a_state: bool,
+    encoder_b_state: bool,
+    // Used to ignore contact bounce on the hold button, which acts on press alone
+    last_hold_toggle: Option<Instant>,
+    // Same for the reset button
+    last_reset_press: Option<Instant>,
+    debouncer: Debouncer,
+    // Fires when a debounce window holding back a button's level ends
+    debounce_timeout: Option<Pin<Box<Sleep>>>,
     // Allows us to check for more button events before executing the action
     tick_timeout: Option<Pin<Box<Sleep>>>,
     // Allows us to re-trigger ourselves when the reset sequnce has elapsed.
@@ -479,17 +5663,29 @@ struct ButtonTracker {
     // Allows us to wait for buttons to be released before counting them
     // after a reset sequence
     reset_debounce: bool,
+    // Fires once the shutdown line has been held for long enough
+    shutdown_timeout: Option<Pin<Box<Sleep>>>,
 }
 impl ButtonTracker {
-    pub fn new(app: Arc<Mutex<ApplicationState>>) -> Self {
+    pub fn new(app: Arc<Mutex<ApplicationState>>, debounce: HashMap<ButtonSide, Duration>) -> Self {
         return Self {
             app,
             left_state: false,
             right_state: false,
             internal_state: false,
+            hold_state: false,
+            reset_state: false,
+            shutdown_state: false,
+            encoder_a_state: false,
+            encoder_b_state: false,
+            last_hold_toggle: None,
+            last_reset_press: None,
+            debouncer: Debouncer::new(debounce),
+            debounce_timeout: None,
             tick_timeout: None,
             reset_timeout: None,
             reset_debounce: false,
+            shutdown_timeout: None,
         };
     }
     pub async fn get_timeout(&mut self) -> TimeoutEvent {
@@ -502,6 +5698,14 @@ impl ButtonTracker {
                 self.reset_timeout = None;
                 TimeoutEvent::ResetTimeout
             },
+            _ = MaybeFuture(self.shutdown_timeout.as_mut()) => {
+                self.shutdown_timeout = None;
+                TimeoutEvent::ShutdownHeld
+            },
+            _ = MaybeFuture(self.debounce_timeout.as_mut()) => {
+                self.debounce_timeout = None;
+                TimeoutEvent::DebounceEnded
+            },
         };
     }
     pub fn timeout_update(&mut self, event: TimeoutEvent) {
@@ -513,7 +5717,7 @@ impl ButtonTracker {
                 );
                 match (self.left_state, self.right_state, self.internal_state) {
                     (false, false, true) if !self.reset_debounce => {
-                        self.reset_timeout = None;
+                        self.cancel_reset_hold();
                         let mut app = self.app.lock().unwrap();
                         if app.game_timer.is_running() {
                             app.freeze();
@@ -522,18 +5726,24 @@ impl ButtonTracker {
                         }
                     }
                     (true, true, false) if !self.reset_debounce => {
-                        self.reset_timeout =
-                            Some(Box::pin(tokio::time::sleep(Duration::from_secs(3))));
+                        let mut app = self.app.lock().unwrap();
+                        self.reset_timeout = app.config.reset_hold_ms.map(|reset_hold_ms| {
+                            Box::pin(tokio::time::sleep(Duration::from_millis(reset_hold_ms)))
+                        });
+                        app.reset_hold_started =
+                            self.reset_timeout.as_ref().map(|_| Instant::now());
                     }
                     (true, false, false) if !self.reset_debounce => {
-                        self.reset_timeout = None;
+                        self.cancel_reset_hold();
                         self.app.lock().unwrap().start_left_timer();
                     }
                     (false, true, false) if !self.reset_debounce => {
-                        self.reset_timeout = None;
+                        self.cancel_reset_hold();
                         self.app.lock().unwrap().start_right_timer();
                     }
                     (false, false, false) => {
+                        // Letting go of both buttons early calls the reset off
+                        self.cancel_reset_hold();
                         self.reset_debounce = false;
                     }
                     _ => {}
@@ -541,86 +5751,714 @@ impl ButtonTracker {
             }
             TimeoutEvent::ResetTimeout => {
                 self.reset_debounce = true;
-                self.app.lock().unwrap().clear_timers();
+                let mut app = self.app.lock().unwrap();
+                app.reset_hold_started = None;
+                app.finish_session();
+            }
+            TimeoutEvent::ShutdownHeld => self.shut_down(),
+            TimeoutEvent::DebounceEnded => {
+                for (side, level) in self.debouncer.settled(Instant::now()) {
+                    self.update(side, level);
+                }
+                self.arm_debounce_timeout();
             }
         }
     }
+    fn arm_debounce_timeout(&mut self) {
+        self.debounce_timeout = self
+            .debouncer
+            .next_settle()
+            .map(|end| Box::pin(tokio::time::sleep_until(end)));
+    }
+    /// Runs the shutdown command on its own thread, so the state isn't locked while it works,
+    /// and allows another try if it fails
+    fn shut_down(&mut self) {
+        let Some(mut command) = self.app.lock().unwrap().shutdown_system() else {
+            return;
+        };
+        let app = self.app.clone();
+        std::thread::spawn(move || {
+            let program = command.get_program().to_owned();
+            let failed = match command.status() {
+                Ok(status) if status.success() => return,
+                Ok(status) => format!("failed with {status}"),
+                Err(err) => format!("couldn't be run: {err}"),
+            };
+            log::error!("Shutdown command {program:?} {failed}");
+            app.lock().unwrap().shutting_down = false;
+        });
+    }
+    fn cancel_reset_hold(&mut self) {
+        self.reset_timeout = None;
+        self.app.lock().unwrap().reset_hold_started = None;
+    }
     pub fn update(&mut self, side: ButtonSide, state: bool) {
+        let now = Instant::now();
+        if self.debouncer.holds_back(side, state, now) {
+            log::debug!("Holding back {side:?} button chatter");
+            self.arm_debounce_timeout();
+            return;
+        }
         let existing_state = match side {
             ButtonSide::Left => &mut self.left_state,
             ButtonSide::Right => &mut self.right_state,
             ButtonSide::Internal => &mut self.internal_state,
+            ButtonSide::Hold => &mut self.hold_state,
+            ButtonSide::Reset => &mut self.reset_state,
+            ButtonSide::Shutdown => &mut self.shutdown_state,
+            ButtonSide::EncoderA => &mut self.encoder_a_state,
+            ButtonSide::EncoderB => &mut self.encoder_b_state,
         };
         if *existing_state == state {
             return;
         }
+        self.debouncer.accepted(side, now);
         log::debug!("{side:?} button set to {state}");
         *existing_state = state;
 
-        self.tick_timeout = Some(Box::pin(tokio::time::sleep(Duration::from_millis(25))));
+        if side == ButtonSide::Hold {
+            if state
+                && self
+                    .last_hold_toggle
+                    .is_none_or(|last_toggle| last_toggle.elapsed() >= HOLD_DEBOUNCE)
+            {
+                self.last_hold_toggle = Some(Instant::now());
+                self.app.lock().unwrap().toggle_hold();
+            }
+            return;
+        }
+        // Each rising edge on A is one click, in the direction given by B
+        if side == ButtonSide::EncoderA {
+            if state {
+                let steps = match self.encoder_b_state {
+                    true => -1,
+                    false => 1,
+                };
+                self.app.lock().unwrap().adjust_time(steps);
+            }
+            return;
+        }
+        if side == ButtonSide::EncoderB {
+            return;
+        }
+        if side == ButtonSide::Shutdown {
+            // Letting go before the hold is up calls the shutdown off
+            self.shutdown_timeout = match state {
+                true => {
+                    let hold = self.app.lock().unwrap().config.gpio.shutdown_hold_ms;
+                    Some(Box::pin(tokio::time::sleep(Duration::from_millis(hold))))
+                }
+                false => None,
+            };
+            return;
+        }
+        if side == ButtonSide::Reset {
+            if state
+                && self
+                    .last_reset_press
+                    .is_none_or(|last_press| last_press.elapsed() >= HOLD_DEBOUNCE)
+            {
+                self.last_reset_press = Some(Instant::now());
+                self.app.lock().unwrap().finish_session();
+            }
+            return;
+        }
+
+        let debounce = Duration::from_millis(self.app.lock().unwrap().config.button_debounce_ms);
+        self.tick_timeout = Some(Box::pin(tokio::time::sleep(debounce)));
     }
 }
 
-async fn track_gpio(timers: Arc<Mutex<ApplicationState>>) {
-    let mut chip = Chip::new("/dev/gpiochip0").unwrap();
-    let mut left_button = AsyncLineEventHandle::new(
-        chip.get_line(23)
-            .unwrap()
-            .events(
-                LineRequestFlags::INPUT | LineRequestFlags::ACTIVE_LOW,
-                EventRequestFlags::BOTH_EDGES,
-                "read-input",
-            )
-            .unwrap(),
-    )
-    .unwrap();
-    let mut right_button = AsyncLineEventHandle::new(
-        chip.get_line(24)
-            .unwrap()
-            .events(
-                LineRequestFlags::INPUT | LineRequestFlags::ACTIVE_LOW,
-                EventRequestFlags::BOTH_EDGES,
-                "read-input",
-            )
-            .unwrap(),
-    )
-    .unwrap();
-    let mut internal_button = AsyncLineEventHandle::new(
-        chip.get_line(17)
-            .unwrap()
-            .events(
-                LineRequestFlags::INPUT | LineRequestFlags::ACTIVE_LOW,
-                EventRequestFlags::RISING_EDGE,
-                "read-input",
-            )
-            .unwrap(),
-    )
-    .unwrap();
+const INDICATOR_INTERVAL: Duration = Duration::from_millis(50);
+// How long a newly dialed shooting time stays on screen
+const ADJUSTMENT_SHOWN: Duration = Duration::from_secs(2);
+
+/// How the app is doing, as blinked on the status LED
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Health {
+    Running,
+    GpioFault,
+    AudioFault,
+}
+impl Health {
+    /// Alternating on and off times in milliseconds for one cycle of the blink pattern
+    pub fn blinks(self) -> &'static [u64] {
+        return match self {
+            // Two short blinks a second, like a heartbeat
+            Health::Running => &[100, 150, 100, 650],
+            Health::GpioFault => &[100, 100],
+            Health::AudioFault => &[800, 200],
+        };
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Light {
+    Red,
+    Amber,
+    Green,
+}
+
+/// Light for a countdown without a sequence: green while it runs, amber in its final
+/// `warning` and red once it's stopped or run out
+fn countdown_light(timer: &Timer, warning: Duration) -> Light {
+    let remaining = timer.get_remaining();
+    if !timer.is_running() || remaining.is_zero() {
+        return Light::Red;
+    }
+    return match remaining <= warning {
+        true => Light::Amber,
+        false => Light::Green,
+    };
+}
+
+/// A physical red/amber/green light stack
+struct TrafficLight {
+    red: gpio_chip::Output,
+    amber: gpio_chip::Output,
+    green: gpio_chip::Output,
+}
+impl TrafficLight {
+    pub fn new(chip: &mut Chip, config: &TrafficLightConfig) -> Result<Self, String> {
+        let mut request = |line: &LineId| chip.request_output(line.offset(), "traffic-light");
+        return Ok(Self {
+            red: request(&config.red_line)?,
+            amber: request(&config.amber_line)?,
+            green: request(&config.green_line)?,
+        });
+    }
+    pub fn show(&self, light: Light) {
+        for (handle, color) in [
+            (&self.red, Light::Red),
+            (&self.amber, Light::Amber),
+            (&self.green, Light::Green),
+        ] {
+            if let Err(err) = handle.set(light == color) {
+                log::warn!("Failed to set traffic light line: {err}");
+            }
+        }
+    }
+}
+
+/// An output on either the GPIO chip or an I2C expander
+enum OutputLine {
+    Chip(gpio_chip::Output),
+    Expander(Arc<Expander>, u32),
+}
+impl OutputLine {
+    pub fn set(&self, on: bool) -> Result<(), String> {
+        return match self {
+            OutputLine::Chip(handle) => handle.set(on),
+            OutputLine::Expander(expander, pin) => {
+                expander.set(*pin, on).map_err(|err| err.to_string())
+            }
+        };
+    }
+}
+
+/// Output lines mirroring which timers are running
+struct Indicators {
+    game: Option<OutputLine>,
+    left: Option<OutputLine>,
+    right: Option<OutputLine>,
+}
+impl Indicators {
+    pub fn new(
+        chip: &mut Chip,
+        expander: Option<&Arc<Expander>>,
+        config: &IndicatorConfig,
+    ) -> Result<Self, String> {
+        let mut request = |line: &Option<LineId>| -> Result<Option<OutputLine>, String> {
+            let Some(line) = line else {
+                return Ok(None);
+            };
+            return Ok(Some(match expander {
+                Some(expander) => OutputLine::Expander(Arc::clone(expander), line.offset()),
+                None => OutputLine::Chip(chip.request_output(line.offset(), "timer-indicator")?),
+            }));
+        };
+        return Ok(Self {
+            game: request(&config.game_line)?,
+            left: request(&config.left_line)?,
+            right: request(&config.right_line)?,
+        });
+    }
+    pub fn update(&self, state: &ApplicationState) {
+        for (handle, running) in [
+            (&self.game, state.game_timer.is_running()),
+            (&self.left, state.side_is_running(Side::Left)),
+            (&self.right, state.side_is_running(Side::Right)),
+        ] {
+            if let Some(handle) = handle {
+                if let Err(err) = handle.set(running) {
+                    log::warn!("Failed to set indicator line: {err}");
+                }
+            }
+        }
+    }
+}
+
+/// A button changing state, or why the buttons can no longer be read
+type ButtonEvent = Result<(ButtonSide, bool), String>;
+type ButtonStream = futures::stream::LocalBoxStream<'static, ButtonEvent>;
+
+/// Which edges a button input reports
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Edges {
+    Rising,
+    Both,
+}
+
+/// Every button input that's configured, with the edges it should report
+fn button_inputs(config: &Config) -> Vec<(InputLine, ButtonSide, Edges)> {
+    let gpio = &config.gpio;
+    let mut inputs = vec![
+        (gpio.left_line.clone(), ButtonSide::Left, Edges::Both),
+        (gpio.right_line.clone(), ButtonSide::Right, Edges::Both),
+        (gpio.game_line.clone(), ButtonSide::Internal, Edges::Rising),
+    ];
+    if let Some(line) = config.hold_line() {
+        inputs.push((line, ButtonSide::Hold, Edges::Both));
+    }
+    if let Some(ref line) = gpio.reset_line {
+        inputs.push((line.clone(), ButtonSide::Reset, Edges::Both));
+    }
+    if let Some(ref line) = gpio.shutdown_line {
+        inputs.push((line.clone(), ButtonSide::Shutdown, Edges::Both));
+    }
+    if let Some(ref encoder) = gpio.encoder {
+        inputs.push((encoder.a_line.clone(), ButtonSide::EncoderA, Edges::Both));
+        inputs.push((encoder.b_line.clone(), ButtonSide::EncoderB, Edges::Both));
+    }
+    return inputs;
+}
+
+/// Everything on the GPIO side that follows the timer state
+struct Outputs {
+    indicators: Indicators,
+    buzzer: Option<(Buzzer, BuzzerConfig)>,
+    traffic_light: Option<TrafficLight>,
+    led_strip: Option<LedStrip>,
+    seven_segment: Option<SevenSegment>,
+    buzzer_signals: Option<futures::channel::mpsc::UnboundedReceiver<BuzzerSignal>>,
+}
+impl Outputs {
+    /// Returns where to send buzzer signals, if there's a buzzer to sound
+    pub fn listen(&mut self) -> Option<futures::channel::mpsc::UnboundedSender<BuzzerSignal>> {
+        self.buzzer.as_ref()?;
+        let (sender, receiver) = futures::channel::mpsc::unbounded();
+        self.buzzer_signals = Some(receiver);
+        return Some(sender);
+    }
+    pub fn update(&mut self, state: &ApplicationState) {
+        self.indicators.update(state);
+        if let Some(ref traffic_light) = self.traffic_light {
+            traffic_light.show(state.light());
+        }
+        if let Some(ref mut led_strip) = self.led_strip {
+            let duration = state.game_timer.get_duration();
+            let remaining = match duration.is_zero() {
+                true => 1.0,
+                false => state.game_timer.get_remaining().as_secs_f64() / duration.as_secs_f64(),
+            };
+            if let Err(err) = led_strip.show(remaining, state.light()) {
+                log::warn!("Failed to update the LED strip: {err}");
+            }
+        }
+        if let Some(ref mut seven_segment) = self.seven_segment {
+            let time_format = &state.config.time_format;
+            let side_text = |side: Side, overtime: Duration| {
+                return match overtime.is_zero() {
+                    true => time_format.format(state.side_display(side).as_millis(), false),
+                    false => format!("-{}", time_format.format(overtime.as_millis(), false)),
+                };
+            };
+            let texts: Vec<String> = seven_segment
+                .modules()
+                .iter()
+                .map(|timer| {
+                    return match timer {
+                        SegmentTimer::Left => {
+                            side_text(Side::Left, state.left_timer.get_overtime())
+                        }
+                        SegmentTimer::Game => {
+                            time_format.format(state.game_timer.get_remaining().as_millis(), false)
+                        }
+                        SegmentTimer::Right => {
+                            side_text(Side::Right, state.right_timer.get_overtime())
+                        }
+                    };
+                })
+                .collect();
+            if let Err(err) = seven_segment.show(&texts) {
+                log::warn!("Failed to update the 7-segment display: {err}");
+            }
+        }
+        if let (Some(ref mut signals), Some((ref mut buzzer, ref config))) =
+            (&mut self.buzzer_signals, &mut self.buzzer)
+        {
+            // Each pattern cuts the one before short, so a restart only sounds the start
+            while let Ok(signal) = signals.try_recv() {
+                match signal {
+                    BuzzerSignal::Start => buzzer.play(&config.start),
+                    BuzzerSignal::Stop => buzzer.play(&config.stop),
+                    BuzzerSignal::Reset => buzzer.play(&config.reset),
+                }
+            }
+        }
+    }
+}
 
-    let mut button_tracker = ButtonTracker::new(timers);
+/// Opens the GPIO chip (and expander, if any) and requests every configured line
+fn open_gpio(config: &mut Config) -> Result<(ButtonStream, Outputs), String> {
+    let mut chip = Chip::open(&config.gpio.chip)?;
+    config.gpio.resolve_names(&chip)?;
+    let inputs = button_inputs(config);
+    let gpio = &config.gpio;
+    let expander = match gpio.expander {
+        Some(ref config) => {
+            let buttons: Vec<InputLine> = inputs.iter().map(|(line, _, _)| line.clone()).collect();
+            let indicators = &gpio.indicators;
+            let outputs: Vec<u32> = [
+                &indicators.game_line,
+                &indicators.left_line,
+                &indicators.right_line,
+            ]
+            .into_iter()
+            .flatten()
+            .map(LineId::offset)
+            .collect();
+            let expander = Expander::open(config, &buttons, &outputs)
+                .map_err(|err| format!("failed to open the GPIO expander: {err}"))?;
+            Some(expander)
+        }
+        None => None,
+    };
+
+    let buttons = match (&expander, &gpio.expander) {
+        (Some(expander), Some(config)) => expander::watch_buttons(
+            Arc::clone(expander),
+            inputs
+                .into_iter()
+                .map(|(line, side, _)| (line, side))
+                .collect(),
+            Duration::from_millis(config.poll_ms),
+        )
+        .map(Ok)
+        .boxed_local(),
+        _ => chip.watch_buttons(inputs)?,
+    };
+
+    let indicators = Indicators::new(&mut chip, expander.as_ref(), &gpio.indicators)
+        .map_err(|err| format!("failed to request the indicator lines: {err}"))?;
+    let buzzer = gpio.buzzer.as_ref().and_then(|config| {
+        return match Buzzer::new(&mut chip, config.line.offset()) {
+            Ok(buzzer) => Some((buzzer, config.clone())),
+            Err(err) => {
+                log::error!("Failed to open buzzer line {}: {err}", config.line);
+                None
+            }
+        };
+    });
+    let traffic_light = gpio.traffic_light.as_ref().and_then(|config| {
+        return match TrafficLight::new(&mut chip, config) {
+            Ok(traffic_light) => Some(traffic_light),
+            Err(err) => {
+                log::error!("Failed to open traffic light lines: {err}");
+                None
+            }
+        };
+    });
+    let led_strip = gpio.led_strip.as_ref().and_then(|config| {
+        return match LedStrip::open(config) {
+            Ok(led_strip) => Some(led_strip),
+            Err(err) => {
+                log::error!("Failed to open LED strip {:?}: {err}", config.device);
+                None
+            }
+        };
+    });
+    let seven_segment = gpio.seven_segment.as_ref().and_then(|config| {
+        return match SevenSegment::open(config) {
+            Ok(seven_segment) => Some(seven_segment),
+            Err(err) => {
+                log::error!(
+                    "Failed to open 7-segment display {:?}: {err}",
+                    config.device
+                );
+                None
+            }
+        };
+    });
+    let outputs = Outputs {
+        indicators,
+        buzzer,
+        traffic_light,
+        led_strip,
+        seven_segment,
+        buzzer_signals: None,
+    };
+    return Ok((buttons, outputs));
+}
+
+/// Opens the GPIO buttons and outputs, or the simulator standing in for them
+fn open_hardware(
+    timers: &Arc<Mutex<ApplicationState>>,
+) -> Result<(ButtonStream, Option<Outputs>), String> {
+    let mut config = timers.lock().unwrap().config.clone();
+    return match config.gpio.simulator {
+        Some(ref simulator) => Ok((
+            simulator::watch_buttons(simulator.clone())
+                .map(Ok)
+                .boxed_local(),
+            None,
+        )),
+        None => {
+            let (buttons, outputs) = open_gpio(&mut config)?;
+            Ok((buttons, Some(outputs)))
+        }
+    };
+}
+
+/// Requests the status LED on its own, so it keeps blinking while the other lines fail
+fn open_status_led(gpio: &mut GpioConfig) -> Result<Option<gpio_chip::Output>, String> {
+    let mut chip = Chip::open(&gpio.chip)?;
+    gpio.resolve_names(&chip)?;
+    let Some(ref line) = gpio.status_line else {
+        return Ok(None);
+    };
+    let handle = chip
+        .request_output(line.offset(), "timer-status")
+        .map_err(|err| format!("failed to request status LED line {line}: {err}"))?;
+    return Ok(Some(handle));
+}
+
+/// Blinks the status LED in a pattern showing the app's health, so installs without a
+/// visible desktop can still be diagnosed
+async fn blink_status(timers: Arc<Mutex<ApplicationState>>) {
+    let mut gpio = timers.lock().unwrap().config.gpio.clone();
+    if gpio.status_line.is_none() || gpio.simulator.is_some() {
+        return;
+    }
+    let mut led = None;
+    loop {
+        let Some(ref handle) = led else {
+            match open_status_led(&mut gpio) {
+                Ok(Some(handle)) => led = Some(handle),
+                Ok(None) => return,
+                Err(err) => {
+                    log::error!("Status LED unavailable, retrying in {GPIO_RETRY_MAX:?}: {err}");
+                    tokio::time::sleep(GPIO_RETRY_MAX).await;
+                }
+            }
+            continue;
+        };
+        let health = timers.lock().unwrap().health();
+        for (i, &ms) in health.blinks().iter().enumerate() {
+            if let Err(err) = handle.set(i % 2 == 0) {
+                log::warn!("Failed to set the status LED: {err}");
+                led = None;
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(ms)).await;
+        }
+    }
+}
+
+const GPIO_RETRY_MIN: Duration = Duration::from_secs(1);
+const GPIO_RETRY_MAX: Duration = Duration::from_secs(30);
+
+/// Buttons that don't go through the GPIO chip: input devices and the wireless receiver
+fn device_buttons(
+    gpio: &GpioConfig,
+) -> futures::stream::LocalBoxStream<'static, (ButtonSide, bool)> {
+    let buttons = evdev::watch_devices(&gpio.input_devices);
+    return match gpio.serial {
+        Some(ref serial) => {
+            futures::stream::select(buttons, serial::watch_receiver(serial.clone())).boxed_local()
+        }
+        None => buttons,
+    };
+}
+
+/// Feeds every button source into one tracker, so presses on different inputs still combine.
+/// Input devices are read from the start; the GPIO side, if `use_gpio`, is opened alongside
+/// them and reopened after longer and longer waits whenever it fails.
+async fn track_buttons(timers: Arc<Mutex<ApplicationState>>, use_gpio: bool) {
+    if use_gpio {
+        tokio::spawn(blink_status(Arc::clone(&timers)));
+    }
+    let config = timers.lock().unwrap().config.clone();
+    let mut device_buttons = device_buttons(&config.gpio);
+    // Lines the kernel debounces aren't debounced again, which would only add latency
+    let debounce = match gpio_chip::DEBOUNCES_IN_KERNEL && config.gpio.expander.is_none() {
+        true => HashMap::new(),
+        false => button_inputs(&config)
+            .iter()
+            .filter_map(|(line, side, _)| line.debounce().map(|window| (*side, window)))
+            .collect(),
+    };
+    let mut button_tracker = ButtonTracker::new(Arc::clone(&timers), debounce);
+    let mut gpio_buttons: Option<ButtonStream> = None;
+    let mut outputs: Option<Outputs> = None;
+    // When to next try opening the GPIO side
+    let mut reopen = use_gpio.then(|| Box::pin(tokio::time::sleep(Duration::ZERO)));
+    let mut backoff = GPIO_RETRY_MIN;
+    let mut indicator_interval = tokio::time::interval(INDICATOR_INTERVAL);
     loop {
-        tokio::select! {
+        let failure = tokio::select! {
             event = button_tracker.get_timeout() => {
                 button_tracker.timeout_update(event);
+                None
             }
-            Some(Ok(left_button)) = left_button.next() => {
-                button_tracker.update(
-                    ButtonSide::Left,
-                    left_button.event_type() == gpio_cdev::EventType::RisingEdge,
-                );
-            },
-            Some(Ok(right_button)) = right_button.next() => {
-                button_tracker.update(
-                    ButtonSide::Right,
-                    right_button.event_type() == gpio_cdev::EventType::RisingEdge,
-                );
+            _ = MaybeFuture(reopen.as_mut()) => {
+                reopen = None;
+                match open_hardware(&timers) {
+                    Ok((buttons, mut opened)) => {
+                        let mut state = timers.lock().unwrap();
+                        state.gpio_error = None;
+                        state.buzzer_signals = opened.as_mut().and_then(Outputs::listen);
+                        backoff = GPIO_RETRY_MIN;
+                        gpio_buttons = Some(buttons);
+                        outputs = opened;
+                        None
+                    }
+                    Err(err) => Some(err),
+                }
+            }
+            _ = indicator_interval.tick(), if outputs.is_some() => {
+                if let Some(ref mut outputs) = outputs {
+                    outputs.update(&timers.lock().unwrap());
+                }
+                None
+            }
+            event = MaybeFuture(gpio_buttons.as_mut().map(StreamExt::next)) => match event {
+                Some(Ok((side, pressed))) => {
+                    button_tracker.update(side, pressed);
+                    None
+                }
+                Some(Err(err)) => Some(err),
+                None => Some("button events stopped".to_owned()),
             },
-            Some(Ok(internal_button)) = internal_button.next() => {
-                button_tracker.update(
-                    ButtonSide::Internal,
-                    internal_button.event_type() == gpio_cdev::EventType::RisingEdge,
-                );
+            Some((side, pressed)) = device_buttons.next() => {
+                button_tracker.update(side, pressed);
+                None
             }
+        };
+        if let Some(err) = failure {
+            log::error!("GPIO unavailable, retrying in {backoff:?}: {err}");
+            let mut state = timers.lock().unwrap();
+            state.gpio_error = Some(err);
+            state.buzzer_signals = None;
+            drop(state);
+            gpio_buttons = None;
+            outputs = None;
+            reopen = Some(Box::pin(tokio::time::sleep(backoff)));
+            backoff = (backoff * 2).min(GPIO_RETRY_MAX);
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn light_turns_red_past_expiry() {
+        let warning = Duration::from_secs(30);
+        let mut timer = Timer::new(Duration::from_secs(60));
+        timer.start();
+        assert_eq!(countdown_light(&timer, warning), Light::Green);
+        timer.restore(Duration::from_secs(45), true);
+        assert_eq!(countdown_light(&timer, warning), Light::Amber);
+        // Still running, as nothing has stopped it yet
+        timer.restore(Duration::from_secs(61), true);
+        assert!(timer.is_running());
+        assert_eq!(countdown_light(&timer, warning), Light::Red);
+    }
+
+    #[test]
+    fn participants_found_by_header() {
+        let list = "Bib\tName\tCountry\tTarget\n\
+                    101\tAnna Berg\tSWE\t012A\n\
+                    102\tBen Cole\tGBR\t\n\
+                    103\tCara Diaz\tESP\t12b\n";
+        let participants = participants::parse_participants(list.as_bytes()).unwrap();
+        assert_eq!(participants.len(), 2);
+        let archer = participants::find_participant(&participants, "12A").unwrap();
+        assert_eq!(
+            (archer.name.as_str(), archer.country.as_str()),
+            ("Anna Berg", "SWE")
+        );
+        let archer = participants::find_participant(&participants, "012B").unwrap();
+        assert_eq!(archer.name, "Cara Diaz");
+    }
+
+    #[test]
+    fn participants_read_from_ianseo_export() {
+        let list = include_str!("../testdata/ianseo-participants.txt");
+        let participants = participants::parse_participants(list.as_bytes()).unwrap();
+        // The compound archer without a target yet is left out
+        assert_eq!(participants.len(), 3);
+        let archer = participants::find_participant(&participants, "1A").unwrap();
+        assert_eq!(
+            (archer.name.as_str(), archer.country.as_str()),
+            ("Anna BERG", "SWE")
+        );
+        let archer = participants::find_participant(&participants, "12b").unwrap();
+        assert_eq!(archer.name, "Dan EVANS");
+        assert!(participants::find_participant(&participants, "2A").is_none());
+    }
+
+    #[test]
+    fn time_format_wraps_only_below_larger_units() {
+        let ms = |secs: u128| secs * 1000;
+        let format = |template: &str, timestamp_ms| {
+            return TimeFormat::parse(template)
+                .unwrap()
+                .format(timestamp_ms, false);
+        };
+        assert_eq!(format("{m:02}:{s:02}", ms(3725)), "62:05");
+        assert_eq!(format("{h}:{m:02}:{s:02}", ms(3725)), "1:02:05");
+        assert_eq!(format("{s}", ms(125)), "125");
+        assert_eq!(format("{s}.{d}", 125_400), "125.4");
+        assert_eq!(format("{m}", ms(125)), "2");
+        assert_eq!(
+            TimeFormat::parse("{s:3}s").unwrap().format(ms(7), true),
+            "  7s.0"
+        );
+    }
+
+    #[test]
+    fn time_format_escapes_braces() {
+        let format = TimeFormat::parse("{{{m}}} {{s}}").unwrap();
+        assert_eq!(format.format(61_000, false), "{1} {s}");
+        assert!(TimeFormat::parse("{m} }").is_err());
+        assert!(TimeFormat::parse("{m").is_err());
+        assert!(TimeFormat::parse("{x}").is_err());
+    }
+
+    #[test]
+    fn release_inside_debounce_window_is_passed_on() {
+        let side = ButtonSide::Left;
+        let mut debouncer = Debouncer::new(HashMap::from([(side, Duration::from_millis(25))]));
+        let start = Instant::now();
+        let at = |ms| start + Duration::from_millis(ms);
+        assert!(!debouncer.holds_back(side, true, start));
+        debouncer.accepted(side, start);
+        // Bouncing on the press, then let go before the window is up
+        assert!(debouncer.holds_back(side, false, at(3)));
+        assert!(debouncer.holds_back(side, true, at(6)));
+        assert!(debouncer.holds_back(side, false, at(10)));
+        assert_eq!(debouncer.next_settle(), Some(at(25)));
+        assert!(debouncer.settled(at(20)).is_empty());
+        assert_eq!(debouncer.settled(at(25)), vec![(side, false)]);
+        assert_eq!(debouncer.next_settle(), None);
+        // A burst ending back where it started hands back the level already acted on
+        assert!(!debouncer.holds_back(side, false, at(25)));
+        debouncer.accepted(side, at(25));
+        assert!(debouncer.holds_back(side, true, at(30)));
+        assert!(debouncer.holds_back(side, false, at(35)));
+        assert_eq!(debouncer.settled(at(50)), vec![(side, false)]);
+    }
+}
@@ -0,0 +1,174 @@
+use std::{
+    fs::File,
+    io::{BufRead, BufReader, Write},
+    path::Path,
+    time::Duration,
+};
+
+use crate::{
+    scoring::{Arrow, SideScore},
+    session::Session,
+    Side,
+};
+
+// Columns of the tab-separated participant list Ianseo imports, and writes out from its
+// participant list export: code, session, division, class, target, four participation flags,
+// mixed team flag, family name, given name, gender, country code, country name and so on.
+// There's no header row.
+const IANSEO_TARGET_COLUMN: usize = 4;
+const IANSEO_FAMILY_NAME_COLUMN: usize = 10;
+const IANSEO_GIVEN_NAME_COLUMN: usize = 11;
+const IANSEO_COUNTRY_CODE_COLUMN: usize = 13;
+
+/// An archer assigned to a target position
+#[derive(Debug, Clone)]
+pub struct Participant {
+    /// Target number and letter, e.g. `12A`
+    pub target: String,
+    pub name: String,
+    pub country: String,
+}
+
+/// Target numbers are often padded with zeros (`012A`), so compare without them
+fn normalize_target(target: &str) -> String {
+    return target.trim().trim_start_matches('0').to_uppercase();
+}
+
+/// Reads the archers out of a participant list file
+pub fn load_participants(path: &Path) -> std::io::Result<Vec<Participant>> {
+    return parse_participants(BufReader::new(File::open(path)?));
+}
+
+/// Reads a tab-separated participant list: either one exported from Ianseo, or one saved from
+/// a spreadsheet whose first row names the columns. A named list needs `target` and `name`
+/// columns and may have a `country` one, in any order and alongside any others. Rows without
+/// a target are skipped.
+pub fn parse_participants(reader: impl BufRead) -> std::io::Result<Vec<Participant>> {
+    let rows = reader.lines().collect::<std::io::Result<Vec<String>>>()?;
+    let headings: Vec<String> = rows
+        .first()
+        .map(|header| {
+            return header
+                .split('\t')
+                .map(|heading| heading.trim().to_lowercase())
+                .collect();
+        })
+        .unwrap_or_default();
+    let find = |name: &str| headings.iter().position(|heading| heading == name);
+    let (rows, columns) = match (find("target"), find("name")) {
+        (Some(target), Some(name)) => (
+            &rows[1..],
+            Columns::Named {
+                target,
+                name,
+                country: find("country"),
+            },
+        ),
+        _ => (&rows[..], Columns::Ianseo),
+    };
+    let mut participants = Vec::new();
+    for row in rows {
+        let fields: Vec<&str> = row.split('\t').collect();
+        let field = |index: usize| fields.get(index).map_or("", |field| field.trim());
+        let (target, name, country) = match columns {
+            Columns::Named {
+                target,
+                name,
+                country,
+            } => (
+                field(target),
+                field(name).to_owned(),
+                country.map_or("", field),
+            ),
+            Columns::Ianseo => (
+                field(IANSEO_TARGET_COLUMN),
+                format!(
+                    "{} {}",
+                    field(IANSEO_GIVEN_NAME_COLUMN),
+                    field(IANSEO_FAMILY_NAME_COLUMN)
+                )
+                .trim()
+                .to_owned(),
+                field(IANSEO_COUNTRY_CODE_COLUMN),
+            ),
+        };
+        if target.is_empty() {
+            continue;
+        }
+        participants.push(Participant {
+            target: normalize_target(target),
+            name,
+            country: country.to_owned(),
+        });
+    }
+    return Ok(participants);
+}
+
+/// Where a participant list keeps each field
+#[derive(Clone, Copy)]
+enum Columns {
+    Named {
+        target: usize,
+        name: usize,
+        country: Option<usize>,
+    },
+    Ianseo,
+}
+
+pub fn find_participant(participants: &[Participant], target: &str) -> Option<Participant> {
+    let target = normalize_target(target);
+    return participants
+        .iter()
+        .find(|participant| participant.target == target)
+        .cloned();
+}
+
+/// Writes one tab-separated row per side per end: target, archer, end, arrows, end total,
+/// running total and the time the side used. This is the timer's own layout, for reading
+/// into a spreadsheet, and not one Ianseo imports.
+pub fn export_results(
+    path: &Path,
+    session: &Session,
+    scores: Option<(&SideScore, &SideScore)>,
+    targets: (&str, &str),
+    archers: (Option<&str>, Option<&str>),
+) -> std::io::Result<()> {
+    let mut file = File::create(path)?;
+    writeln!(file, "Target\tArcher\tEnd\tArrows\tEnd total\tTotal\tTime")?;
+    for side in [Side::Left, Side::Right] {
+        let (target, archer, score) = match side {
+            Side::Left => (targets.0, archers.0, scores.map(|scores| scores.0)),
+            Side::Right => (targets.1, archers.1, scores.map(|scores| scores.1)),
+        };
+        let archer = archer.unwrap_or_default();
+        let scored_ends = score.map_or(&[][..], |score| score.ends());
+        let end_count = scored_ends.len().max(session.ends().len());
+        let mut total = 0;
+        for index in 0..end_count {
+            let time = session
+                .ends()
+                .get(index)
+                .and_then(|end| end.side(side))
+                .map_or(String::new(), format_secs);
+            let (arrows, end_total) = match scored_ends.get(index) {
+                Some(arrows) => {
+                    let end_total: u32 = arrows.iter().map(|arrow| arrow.value()).sum();
+                    total += end_total;
+                    let arrows = arrows.iter().map(Arrow::to_string).collect::<Vec<_>>();
+                    (arrows.join(" "), end_total.to_string())
+                }
+                None => (String::new(), String::new()),
+            };
+            writeln!(
+                file,
+                "{target}\t{archer}\t{}\t{arrows}\t{end_total}\t{total}\t{time}",
+                index + 1
+            )?;
+        }
+    }
+    return Ok(());
+}
+
+fn format_secs(duration: Duration) -> String {
+    return format!("{:.1}", duration.as_secs_f64());
+}
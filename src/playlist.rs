@@ -0,0 +1,87 @@
+use std::path::{Path, PathBuf};
+
+use rand::seq::SliceRandom;
+use serde::{Deserialize, Serialize};
+
+use crate::stream;
+
+// Files picked up from music directories
+pub const AUDIO_EXTENSIONS: [&str; 5] = ["mp3", "wav", "flac", "ogg", "oga"];
+
+/// A music file, a directory of them, or a list of either
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum MusicSource {
+    Path(PathBuf),
+    List(Vec<PathBuf>),
+}
+impl MusicSource {
+    pub fn paths(&self) -> &[PathBuf] {
+        return match self {
+            MusicSource::Path(path) => std::slice::from_ref(path),
+            MusicSource::List(paths) => paths,
+        };
+    }
+    /// The path, if this is a single file rather than a playlist or a stream
+    pub fn file(&self) -> Option<&Path> {
+        return match self {
+            MusicSource::Path(path) if !path.is_dir() && !stream::is_stream(path) => Some(path),
+            _ => None,
+        };
+    }
+    /// Every track, with directories expanded to the audio files in them by name. Streams are
+    /// tracks of their own.
+    pub fn tracks(&self) -> Vec<PathBuf> {
+        let mut tracks = Vec::new();
+        for path in self.paths() {
+            if !path.is_dir() {
+                tracks.push(path.clone());
+                continue;
+            }
+            let entries = match std::fs::read_dir(path) {
+                Ok(entries) => entries,
+                Err(err) => {
+                    log::warn!("Couldn't list music in {path:?}: {err}");
+                    continue;
+                }
+            };
+            let mut files: Vec<PathBuf> = entries
+                .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                .filter(|file| {
+                    file.extension()
+                        .and_then(|extension| extension.to_str())
+                        .is_some_and(|extension| {
+                            AUDIO_EXTENSIONS.contains(&extension.to_ascii_lowercase().as_str())
+                        })
+                })
+                .collect();
+            files.sort();
+            tracks.extend(files);
+        }
+        return tracks;
+    }
+}
+
+/// Where a side has got to in its music
+#[derive(Default)]
+pub struct Playlist {
+    // Tracks still to come, last first
+    queue: Vec<PathBuf>,
+    current: Option<PathBuf>,
+}
+impl Playlist {
+    pub fn current(&self) -> Option<&Path> {
+        return self.current.as_deref();
+    }
+    /// Moves on to the next track, starting over (and reshuffling) once they've all played
+    pub fn advance(&mut self, source: &MusicSource, shuffle: bool) {
+        if self.queue.is_empty() {
+            self.queue = source.tracks();
+            if shuffle {
+                self.queue.shuffle(&mut rand::thread_rng());
+            }
+            self.queue.reverse();
+        }
+        self.current = self.queue.pop();
+    }
+}
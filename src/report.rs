@@ -0,0 +1,141 @@
+use std::{fmt::Write, path::Path, time::Duration};
+
+use crate::{
+    scoring::{Arrow, SideScore},
+    session::FinishedSession,
+    Side,
+};
+
+const STYLE: &str = "
+body { font-family: sans-serif; margin: 2em; }
+table { border-collapse: collapse; margin-bottom: 1em; }
+th, td { border: 1px solid #888; padding: 0.25em 0.75em; text-align: right; }
+th { background-color: #eee; }
+@media print { h2 { page-break-before: auto; } }
+";
+
+fn escape(text: &str) -> String {
+    return text
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;");
+}
+
+fn format_time(duration: Option<Duration>) -> String {
+    return match duration {
+        Some(duration) => {
+            let secs = duration.as_secs();
+            format!(
+                "{}:{:02}.{}",
+                secs / 60,
+                secs % 60,
+                duration.subsec_millis() / 100
+            )
+        }
+        None => "–".to_owned(),
+    };
+}
+
+/// Arrows and end total for an end, if it was scored
+fn format_end_score(score: &SideScore, index: usize) -> (String, String) {
+    let Some(arrows) = score.ends().get(index) else {
+        return (String::new(), String::new());
+    };
+    let total: u32 = arrows.iter().map(|arrow| arrow.value()).sum();
+    let arrows = arrows.iter().map(Arrow::to_string).collect::<Vec<_>>();
+    return (arrows.join(" "), total.to_string());
+}
+
+/// Renders sessions into a standalone HTML page with a table of ends for each
+pub fn render(title: &str, sessions: &[&FinishedSession]) -> String {
+    let mut html = String::new();
+    let title = escape(title);
+    // Writing to a String can't fail
+    let _ = write!(
+        html,
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{title}</title>\n\
+         <style>{STYLE}</style>\n</head>\n<body>\n<h1>{title}</h1>\n"
+    );
+    if sessions.is_empty() {
+        html += "<p>No ends were shot.</p>\n";
+    }
+    for (number, finished) in sessions.iter().enumerate() {
+        let side_name = |side: Side| {
+            let archer = match side {
+                Side::Left => &finished.archers.0,
+                Side::Right => &finished.archers.1,
+            };
+            return escape(archer.as_deref().unwrap_or(match side {
+                Side::Left => "Left",
+                Side::Right => "Right",
+            }));
+        };
+        let _ = write!(
+            html,
+            "<h2>Session {} – finished {}</h2>\n<table>\n<tr><th>End</th>",
+            number + 1,
+            finished.finished_at.format("%H:%M"),
+        );
+        let scores = finished.scores.as_ref();
+        for side in [Side::Left, Side::Right] {
+            let name = side_name(side);
+            let _ = write!(html, "<th>{name} time</th>");
+            if scores.is_some() {
+                let _ = write!(html, "<th>{name} arrows</th><th>{name} score</th>");
+            }
+        }
+        html += "</tr>\n";
+        let scored_ends = scores.map_or(0, |(left, right)| {
+            return left.ends().len().max(right.ends().len());
+        });
+        let end_count = finished.session.ends().len().max(scored_ends);
+        for index in 0..end_count {
+            let record = finished.session.ends().get(index);
+            let end = record.map_or(index + 1, |record| record.end);
+            let _ = write!(html, "<tr><td>{end}</td>");
+            for side in [Side::Left, Side::Right] {
+                let time = format_time(record.and_then(|record| record.side(side)));
+                let _ = write!(html, "<td>{time}</td>");
+                if let Some((left, right)) = scores {
+                    let score = match side {
+                        Side::Left => left,
+                        Side::Right => right,
+                    };
+                    let (arrows, total) = format_end_score(score, index);
+                    let _ = write!(html, "<td>{arrows}</td><td>{total}</td>");
+                }
+            }
+            html += "</tr>\n";
+        }
+        if let Some((left, right)) = scores {
+            let _ = writeln!(
+                html,
+                "<tr><th>Total</th><td></td><td></td><th>{}</th>\
+                 <td></td><td></td><th>{}</th></tr>",
+                left.total(),
+                right.total(),
+            );
+        }
+        html += "</table>\n";
+        for side in [Side::Left, Side::Right] {
+            if let Some(stats) = finished.session.side_stats(side) {
+                let _ = writeln!(
+                    html,
+                    "<p>{}: {} ends, average {}, fastest {}, slowest {}</p>",
+                    side_name(side),
+                    stats.ends,
+                    format_time(Some(stats.average)),
+                    format_time(Some(stats.min)),
+                    format_time(Some(stats.max)),
+                );
+            }
+        }
+    }
+    html += "</body>\n</html>\n";
+    return html;
+}
+
+pub fn save(path: &Path, title: &str, sessions: &[&FinishedSession]) -> std::io::Result<()> {
+    return std::fs::write(path, render(title, sessions));
+}
@@ -0,0 +1,25 @@
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+/// An archer that can be assigned to a side
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Archer {
+    pub name: String,
+    pub club: Option<String>,
+}
+impl Archer {
+    /// Name as shown above a timer, with the club if known
+    pub fn display_name(&self) -> String {
+        return match self.club {
+            Some(ref club) => format!("{} – {club}", self.name),
+            None => self.name.clone(),
+        };
+    }
+}
+
+/// Reads a YAML list of archers
+pub fn load_roster(path: &Path) -> Result<Vec<Archer>, String> {
+    let file = std::fs::File::open(path).map_err(|err| err.to_string())?;
+    return serde_yaml::from_reader(file).map_err(|err| err.to_string());
+}
@@ -0,0 +1,63 @@
+use std::f64::consts::FRAC_PI_2;
+
+use gtk::prelude::*;
+
+/// Puts the display into the window, turned clockwise by a multiple of 90 degrees for screens
+/// mounted on their side. GTK can't rotate widgets itself, so a turned display is laid out in
+/// an offscreen window and painted into the real one. Returns the toplevel the display ends up
+/// in, which is where styles need to be applied and which needs showing along with the window.
+pub fn mount(
+    window: &gtk::ApplicationWindow,
+    child: &impl IsA<gtk::Widget>,
+    degrees: u16,
+) -> gtk::Window {
+    if degrees.is_multiple_of(360) {
+        window.set_child(Some(child));
+        return window.clone().upcast();
+    }
+    let quarter_turns = u32::from(degrees / 90 % 4);
+    let offscreen = gtk::OffscreenWindow::new();
+    offscreen.add(child);
+    let area = gtk::DrawingArea::new();
+    window.set_child(Some(&area));
+    {
+        let offscreen = offscreen.clone();
+        area.connect_size_allocate(move |_, allocation| {
+            let (width, height) = match quarter_turns % 2 {
+                0 => (allocation.width(), allocation.height()),
+                _ => (allocation.height(), allocation.width()),
+            };
+            offscreen.set_size_request(width, height);
+        });
+    }
+    {
+        let offscreen = offscreen.clone();
+        area.connect_draw(move |area, cr| {
+            let Some(surface) = offscreen.surface() else {
+                return glib::Propagation::Proceed;
+            };
+            let (width, height) = (
+                f64::from(area.allocated_width()),
+                f64::from(area.allocated_height()),
+            );
+            match quarter_turns {
+                1 => cr.translate(width, 0.0),
+                2 => cr.translate(width, height),
+                _ => cr.translate(0.0, height),
+            }
+            cr.rotate(FRAC_PI_2 * f64::from(quarter_turns));
+            if let Err(err) = cr
+                .set_source_surface(&surface, 0.0, 0.0)
+                .and_then(|()| cr.paint())
+            {
+                log::warn!("Couldn't draw the rotated display: {err}");
+            }
+            return glib::Propagation::Stop;
+        });
+    }
+    offscreen.connect_damage_event(move |_, _| {
+        area.queue_draw();
+        return false;
+    });
+    return offscreen.upcast();
+}
@@ -0,0 +1,233 @@
+use std::{cmp::Ordering, fmt};
+
+use crate::Side;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Arrow {
+    /// Inner ten, scored as 10 but counted separately for tie-breaks
+    X,
+    Ring(u8),
+    Miss,
+}
+impl Arrow {
+    pub fn value(self) -> u32 {
+        return match self {
+            Arrow::X => 10,
+            Arrow::Ring(ring) => u32::from(ring),
+            Arrow::Miss => 0,
+        };
+    }
+}
+impl fmt::Display for Arrow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return match self {
+            Arrow::X => write!(f, "X"),
+            Arrow::Ring(ring) => write!(f, "{ring}"),
+            Arrow::Miss => write!(f, "M"),
+        };
+    }
+}
+
+/// Arrows one side has scored so far
+#[derive(Debug, Clone, Default)]
+pub struct SideScore {
+    ends: Vec<Vec<Arrow>>,
+    current: Vec<Arrow>,
+}
+impl SideScore {
+    pub fn ends(&self) -> &[Vec<Arrow>] {
+        return &self.ends;
+    }
+    /// Arrows entered for the end that hasn't been filled yet
+    pub fn current(&self) -> &[Arrow] {
+        return &self.current;
+    }
+    pub fn total(&self) -> u32 {
+        return self
+            .ends
+            .iter()
+            .flatten()
+            .chain(&self.current)
+            .map(|arrow| arrow.value())
+            .sum();
+    }
+    fn push(&mut self, arrow: Arrow, arrows_per_end: usize) {
+        self.current.push(arrow);
+        if self.current.len() >= arrows_per_end {
+            self.ends.push(std::mem::take(&mut self.current));
+        }
+    }
+    /// Removes the last arrow entered, reopening the previous end if this one is empty
+    fn undo(&mut self) {
+        if self.current.is_empty() {
+            match self.ends.pop() {
+                Some(end) => self.current = end,
+                None => return,
+            }
+        }
+        self.current.pop();
+    }
+}
+
+/// Set points needed to win a match under the World Archery set system
+pub const SET_POINTS_TO_WIN: u32 = 6;
+
+/// Set points each side has and the match winner, if decided
+#[derive(Debug, Clone, Copy)]
+pub struct SetStandings {
+    pub left: u32,
+    pub right: u32,
+    pub winner: Option<Side>,
+}
+impl SetStandings {
+    pub fn side(&self, side: Side) -> u32 {
+        return match side {
+            Side::Left => self.left,
+            Side::Right => self.right,
+        };
+    }
+}
+
+/// Per-end arrow values for both sides, entered from the keyboard
+pub struct Scoreboard {
+    arrows_per_end: usize,
+    /// Score matches in sets rather than by running total
+    sets: bool,
+    left: SideScore,
+    right: SideScore,
+    entering: Side,
+    // Side each arrow was scored for, most recent last, so undo takes back the right one
+    entered: Vec<Side>,
+}
+impl Scoreboard {
+    pub fn new(arrows_per_end: usize, sets: bool) -> Self {
+        return Self {
+            arrows_per_end: arrows_per_end.max(1),
+            sets,
+            left: SideScore::default(),
+            right: SideScore::default(),
+            entering: Side::Left,
+            entered: Vec::new(),
+        };
+    }
+    pub fn side(&self, side: Side) -> &SideScore {
+        return match side {
+            Side::Left => &self.left,
+            Side::Right => &self.right,
+        };
+    }
+    fn side_mut(&mut self, side: Side) -> &mut SideScore {
+        return match side {
+            Side::Left => &mut self.left,
+            Side::Right => &mut self.right,
+        };
+    }
+    /// Which side the next arrow will be scored for
+    pub fn entering(&self) -> Side {
+        return self.entering;
+    }
+    pub fn switch_side(&mut self) {
+        self.entering = self.entering.other();
+    }
+    /// Set points from every end both sides have finished: 2 for the higher end, 1 each for a
+    /// tie. Ends scored after the match was decided don't count.
+    pub fn set_standings(&self) -> Option<SetStandings> {
+        if !self.sets {
+            return None;
+        }
+        let mut standings = SetStandings {
+            left: 0,
+            right: 0,
+            winner: None,
+        };
+        let end_total = |end: &Vec<Arrow>| end.iter().map(|arrow| arrow.value()).sum::<u32>();
+        for (left, right) in self.left.ends().iter().zip(self.right.ends()) {
+            match end_total(left).cmp(&end_total(right)) {
+                Ordering::Greater => standings.left += 2,
+                Ordering::Less => standings.right += 2,
+                Ordering::Equal => {
+                    standings.left += 1;
+                    standings.right += 1;
+                }
+            }
+            if standings.left >= SET_POINTS_TO_WIN {
+                standings.winner = Some(Side::Left);
+            } else if standings.right >= SET_POINTS_TO_WIN {
+                standings.winner = Some(Side::Right);
+            }
+            if standings.winner.is_some() {
+                break;
+            }
+        }
+        return Some(standings);
+    }
+    /// Scores an arrow, moving on to the other side once this side's end is full
+    pub fn enter(&mut self, arrow: Arrow) {
+        if self
+            .set_standings()
+            .is_some_and(|standings| standings.winner.is_some())
+        {
+            return;
+        }
+        let arrows_per_end = self.arrows_per_end;
+        let side = self.entering;
+        self.side_mut(side).push(arrow, arrows_per_end);
+        self.entered.push(side);
+        if self.side(side).current().is_empty() {
+            self.entering = side.other();
+        }
+    }
+    /// Takes back the last arrow scored, returning to the side it was scored for
+    pub fn undo(&mut self) {
+        let Some(side) = self.entered.pop() else {
+            return;
+        };
+        self.side_mut(side).undo();
+        self.entering = side;
+    }
+    pub fn clear(&mut self) {
+        self.left = SideScore::default();
+        self.right = SideScore::default();
+        self.entering = Side::Left;
+        self.entered.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn undo_after_filling_an_end() {
+        let mut scoreboard = Scoreboard::new(3, false);
+        for arrow in [Arrow::X, Arrow::Ring(9), Arrow::Ring(8)] {
+            scoreboard.enter(arrow);
+        }
+        for _ in 0..3 {
+            scoreboard.enter(Arrow::Ring(7));
+        }
+        for arrow in [Arrow::Ring(10), Arrow::Ring(6), Arrow::Miss] {
+            scoreboard.enter(arrow);
+        }
+        // The left side's second end is full, so entry has moved on to the right
+        assert_eq!(scoreboard.entering(), Side::Right);
+        scoreboard.undo();
+        assert_eq!(scoreboard.entering(), Side::Left);
+        let left = scoreboard.side(Side::Left);
+        assert_eq!(
+            left.ends(),
+            &[vec![Arrow::X, Arrow::Ring(9), Arrow::Ring(8)]]
+        );
+        assert_eq!(left.current(), &[Arrow::Ring(10), Arrow::Ring(6)]);
+        // The right side's finished end is untouched
+        let right = scoreboard.side(Side::Right);
+        assert_eq!(right.ends(), &[vec![Arrow::Ring(7); 3]]);
+        assert_eq!(right.total(), 21);
+        // Going further back returns to the right side's last arrow
+        scoreboard.undo();
+        scoreboard.undo();
+        scoreboard.undo();
+        assert_eq!(scoreboard.entering(), Side::Right);
+        assert_eq!(scoreboard.side(Side::Right).current(), &[Arrow::Ring(7); 2]);
+    }
+}
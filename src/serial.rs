@@ -0,0 +1,105 @@
+use std::{
+    fs::{File, OpenOptions},
+    io, mem,
+    os::{fd::AsRawFd, unix::fs::OpenOptionsExt},
+    path::PathBuf,
+    time::Duration,
+};
+
+use futures::channel::mpsc;
+use serde::{Deserialize, Serialize};
+
+use crate::{simulator, ButtonSide};
+
+// How long to wait before reopening a receiver that failed or was unplugged
+const REOPEN_INTERVAL: Duration = Duration::from_secs(2);
+
+fn default_baud() -> u32 {
+    return 9600;
+}
+
+/// Wireless button receiver on a serial port, sending one event per line in the same form as
+/// the simulator, e.g. `left press`, `game press` or `left down` and `left up`
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SerialConfig {
+    pub device: PathBuf,
+    #[serde(default = "default_baud")]
+    pub baud: u32,
+}
+
+/// The termios speed for a baud rate, if it's one we support
+pub fn baud_speed(baud: u32) -> Option<libc::speed_t> {
+    return match baud {
+        1200 => Some(libc::B1200),
+        2400 => Some(libc::B2400),
+        4800 => Some(libc::B4800),
+        9600 => Some(libc::B9600),
+        19200 => Some(libc::B19200),
+        38400 => Some(libc::B38400),
+        57600 => Some(libc::B57600),
+        115200 => Some(libc::B115200),
+        230400 => Some(libc::B230400),
+        _ => None,
+    };
+}
+
+/// Opens the port raw at the configured speed
+fn open(config: &SerialConfig) -> io::Result<File> {
+    let speed = baud_speed(config.baud).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("unsupported baud rate {}", config.baud),
+        )
+    })?;
+    let file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .custom_flags(libc::O_NOCTTY)
+        .open(&config.device)?;
+    let fd = file.as_raw_fd();
+    // SAFETY: termios is plain data, and is filled in by tcgetattr before it's used
+    let mut termios: libc::termios = unsafe { mem::zeroed() };
+    // SAFETY: every call gets a valid descriptor and a termios we own
+    let result = unsafe {
+        if libc::tcgetattr(fd, &mut termios) < 0 {
+            -1
+        } else {
+            libc::cfmakeraw(&mut termios);
+            libc::cfsetspeed(&mut termios, speed);
+            libc::tcsetattr(fd, libc::TCSANOW, &termios)
+        }
+    };
+    if result < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    return Ok(file);
+}
+
+/// Passes on events from the receiver, reopening it whenever it fails or is unplugged
+pub fn watch_receiver(config: SerialConfig) -> mpsc::UnboundedReceiver<(ButtonSide, bool)> {
+    let (sender, receiver) = mpsc::unbounded();
+    tokio::spawn(async move {
+        let mut failing = false;
+        loop {
+            match open(&config) {
+                Ok(file) => {
+                    log::info!("Reading wireless buttons from {:?}", config.device);
+                    failing = false;
+                    simulator::read_events(tokio::fs::File::from_std(file), sender.clone()).await;
+                    if sender.is_closed() {
+                        return;
+                    }
+                    log::warn!("Lost the wireless receiver on {:?}", config.device);
+                }
+                Err(err) => {
+                    if !failing {
+                        log::warn!("Wireless receiver {:?} unavailable: {err}", config.device);
+                    }
+                    failing = true;
+                }
+            }
+            tokio::time::sleep(REOPEN_INTERVAL).await;
+        }
+    });
+    return receiver;
+}
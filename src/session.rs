@@ -0,0 +1,72 @@
+use std::time::Duration;
+
+use crate::{scoring::SideScore, Side};
+
+/// Time each side used in a completed end
+#[derive(Debug, Clone)]
+pub struct EndRecord {
+    pub end: usize,
+    pub left: Option<Duration>,
+    pub right: Option<Duration>,
+}
+impl EndRecord {
+    pub fn side(&self, side: Side) -> Option<Duration> {
+        return match side {
+            Side::Left => self.left,
+            Side::Right => self.right,
+        };
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct SideStats {
+    pub ends: usize,
+    pub average: Duration,
+    pub min: Duration,
+    pub max: Duration,
+}
+
+/// Every end completed since the application started
+#[derive(Debug, Clone)]
+pub struct Session {
+    ends: Vec<EndRecord>,
+}
+impl Session {
+    pub fn new() -> Self {
+        return Self { ends: Vec::new() };
+    }
+    pub fn record(&mut self, record: EndRecord) {
+        self.ends.push(record);
+    }
+    pub fn ends(&self) -> &[EndRecord] {
+        return &self.ends;
+    }
+    /// Average, fastest and slowest end for a side, if it has shot any
+    pub fn side_stats(&self, side: Side) -> Option<SideStats> {
+        let mut durations = self.ends.iter().filter_map(|end| end.side(side));
+        let first = durations.next()?;
+        let mut stats = SideStats {
+            ends: 1,
+            average: first,
+            min: first,
+            max: first,
+        };
+        let mut total = first;
+        for duration in durations {
+            stats.ends += 1;
+            stats.min = stats.min.min(duration);
+            stats.max = stats.max.max(duration);
+            total += duration;
+        }
+        stats.average = total / stats.ends as u32;
+        return Some(stats);
+    }
+}
+
+/// A session closed out by the reset hold, kept for the summary screen and reports
+pub struct FinishedSession {
+    pub finished_at: chrono::DateTime<chrono::Local>,
+    pub session: Session,
+    pub scores: Option<(SideScore, SideScore)>,
+    pub archers: (Option<String>, Option<String>),
+}
@@ -0,0 +1,295 @@
+use std::{
+    path::Path,
+    sync::{Arc, Mutex},
+};
+
+use gtk::prelude::*;
+use serde_yaml::Value;
+
+use crate::{
+    config_file::ConfigSource, playlist::MusicSource, ApplicationState, TimerConfig, TimerMode,
+};
+
+// The longest time the spin buttons offer, unless the config already has a longer one
+const MAX_SECS: u64 = 24 * 60 * 60;
+
+/// A setting as the dialog shows it, by its path in the config
+type Setting = (Vec<&'static str>, Option<Value>);
+
+fn color_button(color: &str) -> gtk::ColorButton {
+    return match gdk::RGBA::parse(color) {
+        Ok(rgba) => gtk::ColorButton::with_rgba(&rgba),
+        Err(_) => gtk::ColorButton::new(),
+    };
+}
+
+fn file_button(title: &str, path: Option<&Path>) -> gtk::FileChooserButton {
+    let button = gtk::FileChooserButton::new(title, gtk::FileChooserAction::Open);
+    if let Some(path) = path {
+        button.set_filename(path);
+    }
+    return button;
+}
+
+/// A button that clears the file chooser, which has no way of its own to go back to no file
+fn clear_button(chooser: &gtk::FileChooserButton) -> gtk::Button {
+    let button = gtk::Button::from_icon_name(Some("edit-clear"), gtk::IconSize::Button);
+    button.set_tooltip_text(Some("Clear"));
+    let chooser = chooser.clone();
+    button.connect_clicked(move |_| chooser.unselect_all());
+    return button;
+}
+
+fn secs_button(secs: u64) -> gtk::SpinButton {
+    // Widened to fit whatever the config has, so it isn't clamped and written back
+    let button = gtk::SpinButton::with_range(secs.min(1) as f64, secs.max(MAX_SECS) as f64, 1.0);
+    button.set_value(secs as f64);
+    return button;
+}
+
+fn color_value(button: &gtk::ColorButton) -> Option<Value> {
+    return Some(Value::from(button.rgba().to_string()));
+}
+
+fn file_value(button: &gtk::FileChooserButton) -> Option<Value> {
+    return button
+        .filename()
+        .map(|path| Value::from(path.to_string_lossy().into_owned()));
+}
+
+/// Writes the settings that differ from how the dialog started out, so the rest stay as the
+/// config file has them
+fn set_changed(source: &mut ConfigSource, before: &[Setting], after: Vec<Setting>) {
+    for ((path, value), (_, previous)) in after.into_iter().zip(before) {
+        if value != *previous {
+            source.set(&path, value);
+        }
+    }
+}
+
+/// Controls for one side's settings
+struct SideControls {
+    color: gtk::ColorButton,
+    text_color: gtk::ColorButton,
+    music_file: gtk::FileChooserButton,
+    music_clear: gtk::Button,
+    flipped: gtk::CheckButton,
+    countdown: gtk::CheckButton,
+    time_secs: gtk::SpinButton,
+    name: gtk::Entry,
+}
+impl SideControls {
+    pub fn new(config: &TimerConfig) -> Self {
+        let music_file = file_button(
+            "Music",
+            config.music_file.as_ref().and_then(MusicSource::file),
+        );
+        let controls = Self {
+            color: color_button(&config.color),
+            text_color: color_button(&config.text_color),
+            music_clear: clear_button(&music_file),
+            music_file,
+            flipped: gtk::CheckButton::new(),
+            countdown: gtk::CheckButton::new(),
+            time_secs: secs_button(config.time_secs),
+            name: gtk::Entry::new(),
+        };
+        if config.music_file.is_some()
+            && config
+                .music_file
+                .as_ref()
+                .and_then(MusicSource::file)
+                .is_none()
+        {
+            // A single file chooser can't show or keep a playlist or stream
+            controls.music_file.set_sensitive(false);
+            controls.music_clear.set_sensitive(false);
+            controls
+                .music_file
+                .set_tooltip_text(Some("Playlist or stream set in the config file"));
+        }
+        controls
+            .name
+            .set_text(config.name.as_deref().unwrap_or_default());
+        controls.flipped.set_active(config.flipped);
+        controls
+            .countdown
+            .set_active(config.mode == TimerMode::Countdown);
+        return controls;
+    }
+    pub fn attach(&self, grid: &gtk::Grid, column: i32) {
+        grid.attach(&self.color, column, 1, 1, 1);
+        grid.attach(&self.text_color, column, 2, 1, 1);
+        let music = gtk::Box::new(gtk::Orientation::Horizontal, 0);
+        music.pack_start(&self.music_file, true, true, 0);
+        music.pack_start(&self.music_clear, false, false, 0);
+        grid.attach(&music, column, 3, 1, 1);
+        grid.attach(&self.flipped, column, 4, 1, 1);
+        grid.attach(&self.countdown, column, 5, 1, 1);
+        grid.attach(&self.time_secs, column, 6, 1, 1);
+        grid.attach(&self.name, column, 7, 1, 1);
+    }
+    /// The settings as currently shown, under `section` of the config
+    pub fn settings(&self, section: &'static str) -> Vec<Setting> {
+        let mode = match self.countdown.is_active() {
+            true => "countdown",
+            false => "stopwatch",
+        };
+        let name = self.name.text();
+        let mut settings = vec![
+            (vec![section, "color"], color_value(&self.color)),
+            (vec![section, "text_color"], color_value(&self.text_color)),
+            (
+                vec![section, "flipped"],
+                Some(Value::from(self.flipped.is_active())),
+            ),
+            (vec![section, "mode"], Some(Value::from(mode))),
+            (
+                vec![section, "time_secs"],
+                Some(Value::from(self.time_secs.value_as_int())),
+            ),
+            (
+                vec![section, "name"],
+                match name.trim() {
+                    "" => None,
+                    name => Some(Value::from(name)),
+                },
+            ),
+        ];
+        if self.music_file.is_sensitive() {
+            settings.push((vec![section, "music_file"], file_value(&self.music_file)));
+        }
+        return settings;
+    }
+}
+
+/// Edits the most common settings, writing the changed ones back to the config file and
+/// applying them straight away. Flipping a side takes effect after a restart.
+pub fn show_settings_dialog(window: &gtk::ApplicationWindow, timers: Arc<Mutex<ApplicationState>>) {
+    let state = timers.lock().unwrap();
+    let dialog = gtk::Dialog::with_buttons(
+        Some("Settings"),
+        Some(window),
+        gtk::DialogFlags::MODAL | gtk::DialogFlags::DESTROY_WITH_PARENT,
+        &[
+            ("Cancel", gtk::ResponseType::Cancel),
+            ("Save", gtk::ResponseType::Accept),
+        ],
+    );
+    dialog.style_context().add_class("settings");
+    let grid = gtk::Grid::new();
+    grid.set_row_spacing(6);
+    grid.set_column_spacing(12);
+    grid.set_border_width(6);
+    let headings = ["", "Left", "Game", "Right"];
+    for (column, heading) in headings.into_iter().enumerate() {
+        grid.attach(&gtk::Label::new(Some(heading)), column as i32, 0, 1, 1);
+    }
+    let rows = [
+        "Color",
+        "Text color",
+        "Music",
+        "Flipped (after restart)",
+        "Count down",
+        "Time (seconds)",
+        "Archer or team",
+    ];
+    for (row, name) in rows.into_iter().enumerate() {
+        let label = gtk::Label::new(Some(name));
+        label.set_halign(gtk::Align::Start);
+        grid.attach(&label, 0, row as i32 + 1, 1, 1);
+    }
+
+    let left = SideControls::new(&state.config.left_timer);
+    left.attach(&grid, 1);
+    let right = SideControls::new(&state.config.right_timer);
+    right.attach(&grid, 3);
+    let game_color = color_button(&state.config.game_timer.color);
+    let game_text_color = color_button(&state.config.game_timer.text_color);
+    let game_time_secs = secs_button(state.config.game_timer.time_secs);
+    grid.attach(&game_color, 2, 1, 1, 1);
+    grid.attach(&game_text_color, 2, 2, 1, 1);
+    grid.attach(&game_time_secs, 2, 6, 1, 1);
+    drop(state);
+    let settings = move || {
+        let game = vec![
+            (vec!["game_timer", "color"], color_value(&game_color)),
+            (
+                vec!["game_timer", "text_color"],
+                color_value(&game_text_color),
+            ),
+            (
+                vec!["game_timer", "time_secs"],
+                Some(Value::from(game_time_secs.value_as_int())),
+            ),
+        ];
+        return [
+            left.settings("left_timer"),
+            right.settings("right_timer"),
+            game,
+        ]
+        .concat();
+    };
+    let before = settings();
+
+    dialog.content_area().pack_start(&grid, true, true, 6);
+    dialog.connect_response(move |dialog, response| {
+        if response == gtk::ResponseType::Accept {
+            let mut state = timers.lock().unwrap();
+            let previous = state.source.clone();
+            set_changed(&mut state.source, &before, settings());
+            // Only settings that check out are written to the file
+            if !state.apply_source() {
+                state.source = previous;
+            } else if let Err(err) = state.source.save() {
+                log::error!("Failed to save settings to {:?}: {err}", state.source.path);
+            }
+        }
+        dialog.close();
+    });
+    dialog.show_all();
+}
+
+/// Offers to write options changed at runtime back to the config file, so the next start
+/// picks up where this one left off
+pub fn show_save_changes_dialog(
+    window: &gtk::ApplicationWindow,
+    timers: Arc<Mutex<ApplicationState>>,
+) {
+    let changes = timers.lock().unwrap().runtime_changes();
+    if changes.is_empty() {
+        log::info!("No runtime changes to save");
+        return;
+    }
+    let descriptions: Vec<&str> = changes
+        .iter()
+        .map(|change| change.description.as_str())
+        .collect();
+    let dialog = gtk::MessageDialog::new(
+        Some(window),
+        gtk::DialogFlags::MODAL | gtk::DialogFlags::DESTROY_WITH_PARENT,
+        gtk::MessageType::Question,
+        gtk::ButtonsType::None,
+        "Save these changes to the config?",
+    );
+    dialog.style_context().add_class("settings");
+    dialog.set_secondary_text(Some(&descriptions.join("\n")));
+    dialog.add_button("Cancel", gtk::ResponseType::Cancel);
+    dialog.add_button("Save", gtk::ResponseType::Accept);
+    dialog.connect_response(move |dialog, response| {
+        if response == gtk::ResponseType::Accept {
+            let mut state = timers.lock().unwrap();
+            let previous = state.source.clone();
+            for change in &changes {
+                state.source.set(&change.path, change.value.clone());
+            }
+            if !state.apply_source() {
+                state.source = previous;
+            } else if let Err(err) = state.source.save() {
+                log::error!("Failed to save changes to {:?}: {err}", state.source.path);
+            }
+        }
+        dialog.close();
+    });
+    dialog.show_all();
+}
@@ -0,0 +1,148 @@
+use std::{
+    fs::{File, OpenOptions},
+    io::{self, Write},
+    os::fd::AsRawFd,
+    path::PathBuf,
+};
+
+use serde::{Deserialize, Serialize};
+
+// From linux/spi/spidev.h: sets the clock later writes are sent at
+const SPI_IOC_WR_MAX_SPEED_HZ: libc::c_ulong = 0x4004_6B04;
+const SPI_SPEED_HZ: u32 = 1_000_000;
+
+// MAX7219 registers. Digit registers run from 1 (rightmost) to 8.
+const REG_DIGIT0: u8 = 0x01;
+const REG_DECODE_MODE: u8 = 0x09;
+const REG_INTENSITY: u8 = 0x0A;
+const REG_SCAN_LIMIT: u8 = 0x0B;
+const REG_SHUTDOWN: u8 = 0x0C;
+const REG_DISPLAY_TEST: u8 = 0x0F;
+const DIGITS: usize = 8;
+
+// Segment bits in no-decode mode
+const SEGMENT_DP: u8 = 0x80;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SegmentTimer {
+    Left,
+    Game,
+    Right,
+}
+
+fn default_device() -> PathBuf {
+    return PathBuf::from("/dev/spidev0.1");
+}
+fn default_modules() -> Vec<SegmentTimer> {
+    return vec![SegmentTimer::Left, SegmentTimer::Game, SegmentTimer::Right];
+}
+fn default_intensity() -> u8 {
+    return 8;
+}
+
+/// Chained 8-digit MAX7219 modules mirroring the timers, e.g. for a small display facing the
+/// shooting line
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SevenSegmentConfig {
+    #[serde(default = "default_device")]
+    pub device: PathBuf,
+    /// The timer shown on each module, starting with the one wired to the Pi
+    #[serde(default = "default_modules")]
+    pub modules: Vec<SegmentTimer>,
+    /// Brightness from 0 to 15
+    #[serde(default = "default_intensity")]
+    pub intensity: u8,
+}
+
+pub struct SevenSegment {
+    file: File,
+    modules: Vec<SegmentTimer>,
+    // Digits last sent to each module, so unchanged times aren't sent again
+    shown: Vec<[u8; DIGITS]>,
+}
+impl SevenSegment {
+    pub fn open(config: &SevenSegmentConfig) -> io::Result<Self> {
+        let file = OpenOptions::new().write(true).open(&config.device)?;
+        let speed = SPI_SPEED_HZ;
+        // SAFETY: the speed is only read through the pointer, during the call
+        let result = unsafe { libc::ioctl(file.as_raw_fd(), SPI_IOC_WR_MAX_SPEED_HZ, &speed) };
+        if result < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        let mut display = Self {
+            file,
+            modules: config.modules.clone(),
+            shown: vec![[0; DIGITS]; config.modules.len()],
+        };
+        display.write_all(REG_DISPLAY_TEST, 0)?;
+        display.write_all(REG_DECODE_MODE, 0)?;
+        display.write_all(REG_SCAN_LIMIT, DIGITS as u8 - 1)?;
+        display.write_all(REG_INTENSITY, config.intensity.min(15))?;
+        for digit in 0..DIGITS as u8 {
+            display.write_all(REG_DIGIT0 + digit, 0)?;
+        }
+        display.write_all(REG_SHUTDOWN, 1)?;
+        return Ok(display);
+    }
+    pub fn modules(&self) -> &[SegmentTimer] {
+        return &self.modules;
+    }
+    /// Sets the same register on every module in the chain
+    fn write_all(&mut self, register: u8, value: u8) -> io::Result<()> {
+        let frame = [register, value].repeat(self.modules.len());
+        return self.file.write_all(&frame);
+    }
+    /// Shows a time on each module, right-aligned, in the order of `modules`
+    pub fn show(&mut self, texts: &[String]) -> io::Result<()> {
+        let digits: Vec<[u8; DIGITS]> = texts.iter().map(|text| render(text)).collect();
+        if digits == self.shown {
+            return Ok(());
+        }
+        for digit in 0..DIGITS {
+            // The first bytes shifted in end up in the module furthest down the chain
+            let mut frame = Vec::with_capacity(digits.len() * 2);
+            for module in digits.iter().rev() {
+                frame.extend([REG_DIGIT0 + digit as u8, module[digit]]);
+            }
+            self.file.write_all(&frame)?;
+        }
+        self.shown = digits;
+        return Ok(());
+    }
+}
+
+/// Turns text into segments for each digit, rightmost first. Colons and full stops light
+/// the decimal point of the digit before them.
+fn render(text: &str) -> [u8; DIGITS] {
+    let mut glyphs: Vec<u8> = Vec::new();
+    for c in text.chars() {
+        let glyph = match c {
+            '0' => 0x7E,
+            '1' => 0x30,
+            '2' => 0x6D,
+            '3' => 0x79,
+            '4' => 0x33,
+            '5' => 0x5B,
+            '6' => 0x5F,
+            '7' => 0x70,
+            '8' => 0x7F,
+            '9' => 0x7B,
+            '-' => 0x01,
+            ':' | '.' => {
+                match glyphs.last_mut() {
+                    Some(last) => *last |= SEGMENT_DP,
+                    None => glyphs.push(SEGMENT_DP),
+                }
+                continue;
+            }
+            _ => 0,
+        };
+        glyphs.push(glyph);
+    }
+    let mut digits = [0; DIGITS];
+    for (digit, glyph) in digits.iter_mut().zip(glyphs.into_iter().rev()) {
+        *digit = glyph;
+    }
+    return digits;
+}
@@ -0,0 +1,97 @@
+use std::{os::unix::fs::FileTypeExt, path::PathBuf, time::Duration};
+
+use futures::channel::mpsc;
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
+
+use crate::ButtonSide;
+
+// How long a `press` holds the button down, long enough to outlast the tick debounce
+const PRESS_DURATION: Duration = Duration::from_millis(100);
+
+fn parse_button(name: &str) -> Option<ButtonSide> {
+    return match name {
+        "left" => Some(ButtonSide::Left),
+        "right" => Some(ButtonSide::Right),
+        "game" => Some(ButtonSide::Internal),
+        "hold" => Some(ButtonSide::Hold),
+        "reset" => Some(ButtonSide::Reset),
+        "shutdown" => Some(ButtonSide::Shutdown),
+        "encoder_a" => Some(ButtonSide::EncoderA),
+        "encoder_b" => Some(ButtonSide::EncoderB),
+        _ => None,
+    };
+}
+
+/// Passes on events read one per line, such as `left down`, `left up` or `game press`
+pub async fn read_events(
+    input: impl AsyncRead + Unpin,
+    sender: mpsc::UnboundedSender<(ButtonSide, bool)>,
+) {
+    let mut lines = BufReader::new(input).lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        let mut words = line.split_whitespace();
+        let (Some(name), action) = (words.next(), words.next().unwrap_or("press")) else {
+            continue;
+        };
+        let Some(side) = parse_button(name) else {
+            log::warn!("Ignoring event for unknown button {name:?}");
+            continue;
+        };
+        let sent = match action {
+            "down" => sender.unbounded_send((side, true)),
+            "up" => sender.unbounded_send((side, false)),
+            "press" => {
+                let sent = sender.unbounded_send((side, true));
+                tokio::time::sleep(PRESS_DURATION).await;
+                sent.and(sender.unbounded_send((side, false)))
+            }
+            _ => {
+                log::warn!("Ignoring unknown action {action:?}, expected down, up or press");
+                continue;
+            }
+        };
+        if sent.is_err() {
+            return;
+        }
+    }
+}
+
+/// Simulated buttons for development machines, driven by events typed on stdin (`-`) or
+/// written to a UNIX socket at `source`, e.g. with `socat - UNIX-CONNECT:PATH`
+pub fn watch_buttons(source: PathBuf) -> mpsc::UnboundedReceiver<(ButtonSide, bool)> {
+    let (sender, receiver) = mpsc::unbounded();
+    if source.as_os_str() == "-" {
+        log::info!("Simulating GPIO buttons from stdin");
+        tokio::spawn(async move {
+            read_events(tokio::io::stdin(), sender.clone()).await;
+            log::info!("Stdin closed, no more simulated button events");
+            // Kept open, or the end of the events would be taken for a failure and stdin
+            // reopened straight into the same end over and over
+            std::future::pending::<()>().await;
+            drop(sender);
+        });
+        return receiver;
+    }
+    tokio::spawn(async move {
+        // A socket left over from a previous run would stop us binding. Anything else at the
+        // path is left alone, and binding reports it.
+        if std::fs::symlink_metadata(&source).is_ok_and(|metadata| metadata.file_type().is_socket())
+        {
+            if let Err(err) = std::fs::remove_file(&source) {
+                log::warn!("Failed to remove old socket {source:?}: {err}");
+            }
+        }
+        let listener = match tokio::net::UnixListener::bind(&source) {
+            Ok(listener) => listener,
+            Err(err) => {
+                log::error!("Failed to listen for simulated buttons on {source:?}: {err}");
+                return;
+            }
+        };
+        log::info!("Simulating GPIO buttons from {source:?}");
+        while let Ok((stream, _)) = listener.accept().await {
+            tokio::spawn(read_events(stream, sender.clone()));
+        }
+    });
+    return receiver;
+}
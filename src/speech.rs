@@ -0,0 +1,135 @@
+use std::{
+    io::{self, Cursor, Write},
+    process::{Command, Stdio},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc, Arc,
+    },
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::SequencePhase;
+
+fn default_command() -> Vec<String> {
+    return vec![
+        "espeak-ng".to_owned(),
+        "--stdout".to_owned(),
+        "{text}".to_owned(),
+    ];
+}
+fn default_preparation() -> Option<String> {
+    return Some("End {end}. Archers to the line.".to_owned());
+}
+fn default_remaining() -> Vec<RemainingAnnouncement> {
+    return vec![RemainingAnnouncement {
+        at_secs: 120,
+        text: "Two minutes remaining".to_owned(),
+    }];
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RemainingAnnouncement {
+    pub at_secs: u64,
+    pub text: String,
+}
+
+/// Spoken announcements from an offline text-to-speech engine. Announcements may contain
+/// `{end}`, which is replaced with the end number.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SpeechConfig {
+    /// Command writing a WAV of the text to stdout. `{text}` in an argument is replaced with
+    /// the text; without one, the text is written to the command's stdin.
+    #[serde(default = "default_command")]
+    pub command: Vec<String>,
+    /// Said as each phase of the sequence begins
+    #[serde(default = "default_preparation")]
+    pub preparation: Option<String>,
+    #[serde(default)]
+    pub shooting: Option<String>,
+    #[serde(default)]
+    pub warning: Option<String>,
+    #[serde(default)]
+    pub end_of_end: Option<String>,
+    #[serde(default, rename = "break")]
+    pub break_between_ends: Option<String>,
+    /// Said as the game timer's remaining time passes each of these while shooting
+    #[serde(default = "default_remaining")]
+    pub remaining: Vec<RemainingAnnouncement>,
+}
+impl SpeechConfig {
+    pub fn phase_text(&self, phase: SequencePhase) -> Option<&str> {
+        let text = match phase {
+            SequencePhase::Idle => &None,
+            SequencePhase::Preparation => &self.preparation,
+            SequencePhase::Shooting => &self.shooting,
+            SequencePhase::Warning => &self.warning,
+            SequencePhase::EndOfEnd => &self.end_of_end,
+            SequencePhase::Break => &self.break_between_ends,
+        };
+        return text.as_deref();
+    }
+}
+
+pub struct Utterance {
+    pub command: Vec<String>,
+    pub text: String,
+    pub volume: f32,
+}
+
+/// Speaks queued utterances one after another on their own thread, since synthesis can take
+/// a moment on slow boards. `speaking` is set while one is being said.
+pub fn spawn(mixer: rodio::mixer::Mixer, speaking: Arc<AtomicBool>) -> mpsc::Sender<Utterance> {
+    let (sender, receiver) = mpsc::channel::<Utterance>();
+    std::thread::spawn(move || {
+        for utterance in receiver {
+            let wav = match synthesize(&utterance.command, &utterance.text) {
+                Ok(wav) => wav,
+                Err(err) => {
+                    log::warn!("Couldn't synthesize {:?}: {err}", utterance.text);
+                    continue;
+                }
+            };
+            match rodio::Decoder::new(Cursor::new(wav)) {
+                Ok(decoder) => {
+                    speaking.store(true, Ordering::Relaxed);
+                    let sink = rodio::Sink::connect_new(&mixer);
+                    sink.set_volume(utterance.volume);
+                    sink.append(decoder);
+                    sink.sleep_until_end();
+                    speaking.store(false, Ordering::Relaxed);
+                }
+                Err(err) => log::warn!("Couldn't decode speech for {:?}: {err}", utterance.text),
+            }
+        }
+    });
+    return sender;
+}
+
+fn synthesize(command: &[String], text: &str) -> io::Result<Vec<u8>> {
+    let Some((program, args)) = command.split_first() else {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "no speech command",
+        ));
+    };
+    let piped = !args.iter().any(|arg| arg.contains("{text}"));
+    let mut child = Command::new(program)
+        .args(args.iter().map(|arg| arg.replace("{text}", text)))
+        .stdin(match piped {
+            true => Stdio::piped(),
+            false => Stdio::null(),
+        })
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        // Announcements are short enough to fit in the pipe, so this can't block on the output
+        stdin.write_all(text.as_bytes())?;
+    }
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        return Err(io::Error::other(format!("exited with {}", output.status)));
+    }
+    return Ok(output.stdout);
+}
@@ -0,0 +1,53 @@
+use std::path::Path;
+
+use rusqlite::{params, Connection};
+
+use crate::session::EndRecord;
+
+/// SQLite database of every session and the ends shot in it
+pub struct SessionStore {
+    conn: Connection,
+    session_id: i64,
+}
+impl SessionStore {
+    /// Opens (or creates) the database and starts a new session in it
+    pub fn open(path: &Path) -> rusqlite::Result<Self> {
+        let conn = Connection::open(path)?;
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS sessions (
+                id INTEGER PRIMARY KEY,
+                started_at TEXT NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS ends (
+                id INTEGER PRIMARY KEY,
+                session_id INTEGER NOT NULL REFERENCES sessions(id),
+                end_number INTEGER NOT NULL,
+                recorded_at TEXT NOT NULL,
+                left_secs REAL,
+                right_secs REAL
+            );",
+        )?;
+        conn.execute(
+            "INSERT INTO sessions (started_at) VALUES (?1)",
+            params![chrono::Local::now().to_rfc3339()],
+        )?;
+        let session_id = conn.last_insert_rowid();
+        return Ok(Self { conn, session_id });
+    }
+    pub fn record_end(&self, record: &EndRecord) {
+        let result = self.conn.execute(
+            "INSERT INTO ends (session_id, end_number, recorded_at, left_secs, right_secs)
+                VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                self.session_id,
+                record.end as i64,
+                chrono::Local::now().to_rfc3339(),
+                record.left.map(|left| left.as_secs_f64()),
+                record.right.map(|right| right.as_secs_f64()),
+            ],
+        );
+        if let Err(err) = result {
+            log::error!("Failed to store end {}: {err}", record.end);
+        }
+    }
+}
@@ -0,0 +1,57 @@
+use std::{
+    io::{self, Read, Seek, SeekFrom},
+    path::Path,
+    process::{Child, ChildStdout, Command, Stdio},
+};
+
+/// Whether a music path is really an `http(s)://` URL, such as an internet radio station
+pub fn is_stream(path: &Path) -> bool {
+    let path = path.to_string_lossy();
+    return path.starts_with("http://") || path.starts_with("https://");
+}
+
+/// Body of a download by curl, which deals with redirects and TLS for us
+pub struct Download {
+    child: Child,
+    stdout: ChildStdout,
+}
+impl Read for Download {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        return self.stdout.read(buf);
+    }
+}
+impl Seek for Download {
+    fn seek(&mut self, _pos: SeekFrom) -> io::Result<u64> {
+        return Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "streams can't seek",
+        ));
+    }
+}
+impl Drop for Download {
+    fn drop(&mut self) {
+        // Hangs up on the server once the music is stopped
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+/// Connects to a stream and starts decoding it, which blocks until the first of it arrives
+pub fn open(url: &Path) -> Result<rodio::Decoder<Download>, String> {
+    let mut child = Command::new("curl")
+        .args(["--silent", "--fail", "--location"])
+        .arg(url)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|err| format!("couldn't run curl: {err}"))?;
+    let stdout = child.stdout.take().unwrap();
+    let mut builder = rodio::Decoder::builder()
+        .with_data(Download { child, stdout })
+        .with_seekable(false);
+    if let Some(extension) = url.extension().and_then(|extension| extension.to_str()) {
+        builder = builder.with_hint(extension);
+    }
+    return builder.build().map_err(|err| err.to_string());
+}
@@ -0,0 +1,66 @@
+use serde::{Deserialize, Serialize};
+
+/// Palette applied over the configured colors, for archers who can't tell them apart.
+/// Reds, ambers and greens are swapped for the theme's stop, warning and go colors, and text
+/// is drawn in black or white, whichever stands out more.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Theme {
+    /// Fully saturated colors
+    HighContrast,
+    /// Safe for deuteranopia and protanopia, the common red-green color blindness
+    RedGreen,
+    /// Safe for tritanopia, where blues and yellows get confused
+    BlueYellow,
+}
+impl Theme {
+    /// Stop, warning and go colors
+    fn palette(self) -> [&'static str; 3] {
+        return match self {
+            Theme::HighContrast => ["#ff0000", "#ffff00", "#00ff00"],
+            // From the Okabe-Ito palette: vermilion, yellow and blue
+            Theme::RedGreen => ["#d55e00", "#f0e442", "#0072b2"],
+            // Vermilion, reddish purple and bluish green
+            Theme::BlueYellow => ["#d55e00", "#cc79a7", "#009e73"],
+        };
+    }
+    /// Background and text colors to show in place of the configured ones
+    pub fn apply(self, color: &str, text_color: &str) -> (String, String) {
+        let Ok(rgba) = gdk::RGBA::parse(color) else {
+            return (color.to_owned(), text_color.to_owned());
+        };
+        let [stop, warning, go] = self.palette();
+        let color = match hue(&rgba) {
+            Some(hue) if !(20.0..340.0).contains(&hue) => stop.to_owned(),
+            Some(hue) if hue < 70.0 => warning.to_owned(),
+            Some(hue) if hue < 170.0 => go.to_owned(),
+            _ => color.to_owned(),
+        };
+        let background = gdk::RGBA::parse(&color).unwrap_or(rgba);
+        let luminance =
+            0.2126 * background.red() + 0.7152 * background.green() + 0.0722 * background.blue();
+        let text_color = match luminance > 0.5 {
+            true => "black",
+            false => "white",
+        };
+        return (color, text_color.to_owned());
+    }
+}
+
+/// Hue in degrees, or None for greys which have none to speak of
+fn hue(rgba: &gdk::RGBA) -> Option<f64> {
+    let (red, green, blue) = (rgba.red(), rgba.green(), rgba.blue());
+    let max = red.max(green).max(blue);
+    let range = max - red.min(green).min(blue);
+    if max == 0.0 || range / max < 0.25 {
+        return None;
+    }
+    let hue = if max == red {
+        (green - blue) / range
+    } else if max == green {
+        (blue - red) / range + 2.0
+    } else {
+        (red - green) / range + 4.0
+    };
+    return Some((hue * 60.0).rem_euclid(360.0));
+}
@@ -0,0 +1,101 @@
+use std::{cell::RefCell, time::Instant};
+
+use gtk::prelude::*;
+use serde::{Deserialize, Serialize};
+
+// Put between messages, and between the end of the list and its start again
+const SEPARATOR: &str = "     •     ";
+
+fn default_scroll() -> bool {
+    return true;
+}
+fn default_speed() -> f64 {
+    return 120.0;
+}
+fn default_interval_secs() -> u64 {
+    return 10;
+}
+
+/// Messages shown in a strip along the bottom of the screen
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TickerConfig {
+    pub messages: Vec<String>,
+    /// Scroll the messages past one after another, rather than showing them one at a time
+    #[serde(default = "default_scroll")]
+    pub scroll: bool,
+    /// Pixels per second the messages scroll at
+    #[serde(default = "default_speed")]
+    pub speed: f64,
+    /// How long each message is shown for when not scrolling
+    #[serde(default = "default_interval_secs")]
+    pub interval_secs: u64,
+}
+
+/// The message strip
+pub struct Ticker {
+    scroller: gtk::ScrolledWindow,
+    label: gtk::Label,
+    started: Instant,
+    // Text being scrolled, its width, and the width of the strip it was repeated to fill
+    scrolling: RefCell<(String, i32, i32)>,
+}
+impl Ticker {
+    pub fn new() -> Self {
+        let scroller = gtk::ScrolledWindow::new(gtk::Adjustment::NONE, gtk::Adjustment::NONE);
+        // Moved along by hand, without a scrollbar
+        scroller.set_policy(gtk::PolicyType::External, gtk::PolicyType::Never);
+        scroller.style_context().add_class("ticker");
+        scroller.set_no_show_all(true);
+        let label = gtk::Label::new(None);
+        label.style_context().add_class("ticker");
+        scroller.add(&label);
+        if let Some(viewport) = scroller.child() {
+            viewport.show();
+        }
+        label.show();
+        return Self {
+            scroller,
+            label,
+            started: Instant::now(),
+            scrolling: RefCell::new((String::new(), 0, 0)),
+        };
+    }
+    pub fn widget(&self) -> &gtk::ScrolledWindow {
+        return &self.scroller;
+    }
+    /// Shows the messages, returning whether they're scrolling and need frequent updates
+    pub fn update(&self, config: Option<&TickerConfig>) -> bool {
+        let Some(config) = config.filter(|config| !config.messages.is_empty()) else {
+            self.scroller.set_visible(false);
+            return false;
+        };
+        self.scroller.set_visible(true);
+        let elapsed = self.started.elapsed().as_secs_f64();
+        if !config.scroll {
+            let index = (elapsed / config.interval_secs.max(1) as f64) as usize;
+            self.label
+                .set_text(&config.messages[index % config.messages.len()]);
+            *self.scrolling.borrow_mut() = (String::new(), 0, 0);
+            self.scroller.hadjustment().set_value(0.0);
+            return false;
+        }
+        let text = format!("{}{SEPARATOR}", config.messages.join(SEPARATOR));
+        let strip_width = self.scroller.allocated_width();
+        let mut scrolling = self.scrolling.borrow_mut();
+        if scrolling.0 != text || scrolling.2 != strip_width {
+            // Repeated so there's always text to fill the strip, wherever it's scrolled to
+            let text_width = self
+                .label
+                .create_pango_layout(Some(&text))
+                .pixel_size()
+                .0
+                .max(1);
+            let repeats = (strip_width / text_width) as usize + 2;
+            self.label.set_text(&text.repeat(repeats));
+            *scrolling = (text, text_width, strip_width);
+        }
+        let offset = (elapsed * config.speed) % f64::from(scrolling.1);
+        self.scroller.hadjustment().set_value(offset);
+        return true;
+    }
+}
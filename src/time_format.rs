@@ -0,0 +1,146 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Unit {
+    Hours,
+    Minutes,
+    Seconds,
+    Tenths,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Piece {
+    Text(String),
+    Field {
+        unit: Unit,
+        width: usize,
+        zero_pad: bool,
+    },
+}
+
+/// How times are written out, from a template such as `{m:02}:{s:02}` or `{h}:{m:02}:{s:02}`.
+/// `h`, `m`, `s` and `d` are hours, minutes, seconds and tenths. Minutes and seconds only wrap
+/// when the template shows a larger unit, so `{s}` alone counts all the seconds. `{{` and `}}`
+/// write out braces.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(try_from = "String", into = "String")]
+pub struct TimeFormat {
+    template: String,
+    pieces: Vec<Piece>,
+}
+impl TimeFormat {
+    pub fn parse(template: &str) -> Result<Self, String> {
+        let mut pieces = Vec::new();
+        let mut text = String::new();
+        let mut rest = template;
+        while let Some(brace) = rest.find(['{', '}']) {
+            text += &rest[..brace];
+            let from_brace = &rest[brace..];
+            if from_brace.starts_with("{{") || from_brace.starts_with("}}") {
+                text += &from_brace[..1];
+                rest = &from_brace[2..];
+                continue;
+            }
+            if from_brace.starts_with('}') {
+                return Err(format!("unmatched }} in {template:?}"));
+            }
+            let end = from_brace
+                .find('}')
+                .ok_or_else(|| format!("unclosed {{ in {template:?}"))?;
+            let field = &from_brace[1..end];
+            let (name, spec) = field.split_once(':').unwrap_or((field, ""));
+            let unit = match name {
+                "h" => Unit::Hours,
+                "m" => Unit::Minutes,
+                "s" => Unit::Seconds,
+                "d" => Unit::Tenths,
+                _ => return Err(format!("unknown field {{{field}}} in {template:?}")),
+            };
+            let width = match spec {
+                "" => 0,
+                spec => spec
+                    .parse()
+                    .map_err(|_| format!("bad width in {{{field}}} in {template:?}"))?,
+            };
+            if !text.is_empty() {
+                pieces.push(Piece::Text(std::mem::take(&mut text)));
+            }
+            pieces.push(Piece::Field {
+                unit,
+                width,
+                zero_pad: spec.starts_with('0'),
+            });
+            rest = &from_brace[end + 1..];
+        }
+        text += rest;
+        if !text.is_empty() {
+            pieces.push(Piece::Text(text));
+        }
+        return Ok(Self {
+            template: template.to_owned(),
+            pieces,
+        });
+    }
+    fn shows(&self, shown: Unit) -> bool {
+        return self
+            .pieces
+            .iter()
+            .any(|piece| matches!(piece, Piece::Field { unit, .. } if *unit == shown));
+    }
+    /// Writes out a time. With `tenths`, templates without `{d}` get tenths appended.
+    pub fn format(&self, timestamp_ms: u128, tenths: bool) -> String {
+        let timestamp_s = timestamp_ms / 1000;
+        let h = timestamp_s / 3600;
+        let m = match self.shows(Unit::Hours) {
+            true => (timestamp_s / 60) % 60,
+            false => timestamp_s / 60,
+        };
+        let s = match self.shows(Unit::Hours) || self.shows(Unit::Minutes) {
+            true => timestamp_s % 60,
+            false => timestamp_s,
+        };
+        let d = (timestamp_ms % 1000) / 100;
+        let mut formatted = String::new();
+        for piece in &self.pieces {
+            match *piece {
+                Piece::Text(ref text) => formatted += text,
+                Piece::Field {
+                    unit,
+                    width,
+                    zero_pad,
+                } => {
+                    let value = match unit {
+                        Unit::Hours => h,
+                        Unit::Minutes => m,
+                        Unit::Seconds => s,
+                        Unit::Tenths => d,
+                    };
+                    formatted += &match zero_pad {
+                        true => format!("{value:0width$}"),
+                        false => format!("{value:width$}"),
+                    };
+                }
+            }
+        }
+        if tenths && !self.shows(Unit::Tenths) {
+            formatted += &format!(".{d}");
+        }
+        return formatted;
+    }
+}
+impl Default for TimeFormat {
+    fn default() -> Self {
+        return Self::parse("{m:02}:{s:02}").unwrap();
+    }
+}
+impl TryFrom<String> for TimeFormat {
+    type Error = String;
+    fn try_from(template: String) -> Result<Self, String> {
+        return Self::parse(&template);
+    }
+}
+impl From<TimeFormat> for String {
+    fn from(format: TimeFormat) -> String {
+        return format.template;
+    }
+}
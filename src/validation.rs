@@ -0,0 +1,379 @@
+use std::{path::Path, time::Duration};
+
+use crate::{
+    expander::ExpanderKind, keybindings::parse_key, parse_wall_time, serial, stream,
+    voice_pack::VoicePack, Config, LineId,
+};
+
+/// Problems with a parsed config that would otherwise only surface as a crash or a
+/// misbehaving display later on
+pub fn validate(config: &Config) -> Vec<String> {
+    let mut problems = Vec::new();
+    let mut check_color = |name: &str, color: &str| {
+        if gdk::RGBA::parse(color).is_err() {
+            problems.push(format!("{name}: {color:?} is not a valid color"));
+        }
+    };
+    check_color("game_timer.color", &config.game_timer.color);
+    check_color("game_timer.text_color", &config.game_timer.text_color);
+    for (side, timer) in [
+        ("left_timer", &config.left_timer),
+        ("right_timer", &config.right_timer),
+    ] {
+        check_color(&format!("{side}.color"), &timer.color);
+        check_color(&format!("{side}.text_color"), &timer.text_color);
+        if let Some(ref prep) = timer.prep {
+            check_color(&format!("{side}.prep.color"), &prep.color);
+        }
+        for threshold in &timer.thresholds {
+            check_color(&format!("{side}.thresholds.color"), &threshold.color);
+        }
+        if let Some(ref colors) = timer.phase_colors {
+            for (phase, color) in [
+                ("prep", &colors.prep),
+                ("shooting", &colors.shooting),
+                ("warning", &colors.warning),
+                ("stopped", &colors.stopped),
+            ] {
+                if let Some(color) = color {
+                    check_color(&format!("{side}.phase_colors.{phase}"), color);
+                }
+            }
+        }
+    }
+    if let Some(ref sequence) = config.sequence {
+        check_color("sequence.prep_color", &sequence.prep_color);
+        check_color("sequence.shooting_color", &sequence.shooting_color);
+        check_color("sequence.warning_color", &sequence.warning_color);
+        check_color("sequence.end_color", &sequence.end_color);
+    }
+    if let Some(ref intervals) = config.intervals {
+        check_color("intervals.shoot_color", &intervals.shoot_color);
+        check_color("intervals.rest_color", &intervals.rest_color);
+    }
+
+    let mut sounds = vec![
+        ("game_timer.start_sfx", &config.game_timer.start_sfx),
+        ("game_timer.end_sfx", &config.game_timer.end_sfx),
+        ("left_timer.end_sfx", &config.left_timer.end_sfx),
+        ("right_timer.end_sfx", &config.right_timer.end_sfx),
+        ("start_sound", &config.start_sound),
+        ("stop_sound", &config.stop_sound),
+        ("reset_sound", &config.reset_sound),
+    ];
+    if let Some(ref prep) = config.left_timer.prep {
+        sounds.push(("left_timer.prep.sfx", &prep.sfx));
+    }
+    if let Some(ref prep) = config.right_timer.prep {
+        sounds.push(("right_timer.prep.sfx", &prep.sfx));
+    }
+    if let Some(ref sequence) = config.sequence {
+        sounds.push(("sequence.prep_sfx", &sequence.prep_sfx));
+        sounds.push(("sequence.shooting_sfx", &sequence.shooting_sfx));
+        sounds.push(("sequence.end_sfx", &sequence.end_sfx));
+    }
+    if let Some(ref alternating) = config.alternating {
+        sounds.push(("alternating.arrow_sfx", &alternating.arrow_sfx));
+        sounds.push(("alternating.timeout_sfx", &alternating.timeout_sfx));
+    }
+    if let Some(ref intervals) = config.intervals {
+        sounds.push(("intervals.shoot_music", &intervals.shoot_music));
+        sounds.push(("intervals.rest_music", &intervals.rest_music));
+    }
+    for (name, path) in sounds {
+        if let Some(path) = path {
+            check_file(&mut problems, name, path);
+        }
+    }
+    for (name, music) in [
+        ("left_timer.music_file", &config.left_timer.music_file),
+        ("right_timer.music_file", &config.right_timer.music_file),
+    ] {
+        let Some(music) = music else {
+            continue;
+        };
+        for path in music.paths() {
+            if !path.exists() && !stream::is_stream(path) {
+                problems.push(format!("{name}: {path:?} does not exist"));
+            }
+        }
+        if music.tracks().is_empty() {
+            problems.push(format!("{name}: no music files found"));
+        }
+    }
+    for (index, milestone) in config.milestones.iter().enumerate() {
+        if let Some(ref sound) = milestone.sound {
+            check_file(&mut problems, &format!("milestones[{index}].sound"), sound);
+        }
+    }
+    if let Some(ref metronome) = config.metronome {
+        match (metronome.bpm, metronome.interval_secs) {
+            (Some(_), Some(_)) => {
+                problems.push("metronome: give either bpm or interval_secs, not both".to_owned())
+            }
+            (None, None) => problems.push("metronome: bpm or interval_secs is needed".to_owned()),
+            _ => {
+                let interval = metronome.interval();
+                if interval.is_none_or(|interval| interval < Duration::from_millis(100)) {
+                    problems.push(
+                        "metronome: must be positive, and click at most 10 times a second"
+                            .to_owned(),
+                    );
+                }
+            }
+        }
+    }
+    if let Some(ref voice_pack) = config.voice_pack {
+        if let Err(err) = VoicePack::load(voice_pack) {
+            problems.push(format!("voice_pack: {voice_pack:?}: {err}"));
+        }
+    }
+    if let Some(ref css) = config.css {
+        check_file(&mut problems, "css", css);
+    }
+    if let Some(ref wall_clock) = config.wall_clock {
+        // Formatting with a bad specifier would panic later on
+        if chrono::format::StrftimeItems::new(&wall_clock.format)
+            .any(|item| item == chrono::format::Item::Error)
+        {
+            problems.push(format!(
+                "wall_clock.format: {:?} is not a valid time format",
+                wall_clock.format
+            ));
+        }
+    }
+    if config
+        .ticker
+        .as_ref()
+        .is_some_and(|ticker| ticker.scroll && ticker.speed <= 0.0)
+    {
+        problems.push("ticker.speed: must be above 0".to_owned());
+    }
+    if let Some(ref logo) = config.logo {
+        check_file(&mut problems, "logo.path", &logo.path);
+        if logo.height <= 0 {
+            problems.push("logo.height: must be above 0".to_owned());
+        }
+    }
+    if let Some(ref roster) = config.roster {
+        check_file(&mut problems, "roster", roster);
+    }
+    if let Some(file) = config
+        .participants
+        .as_ref()
+        .and_then(|participants| participants.file.as_ref())
+    {
+        check_file(&mut problems, "participants.file", file);
+    }
+
+    let mut lines = vec![
+        ("gpio.left_line", config.gpio.left_line.line().clone()),
+        ("gpio.right_line", config.gpio.right_line.line().clone()),
+        ("gpio.game_line", config.gpio.game_line.line().clone()),
+    ];
+    if let Some(hold_line) = config.hold_line() {
+        lines.push(("gpio.hold_line", hold_line.line().clone()));
+    }
+    if let Some(ref reset_line) = config.gpio.reset_line {
+        lines.push(("gpio.reset_line", reset_line.line().clone()));
+    }
+    if let Some(ref shutdown_line) = config.gpio.shutdown_line {
+        lines.push(("gpio.shutdown_line", shutdown_line.line().clone()));
+    }
+    if let Some(ref encoder) = config.gpio.encoder {
+        lines.push(("gpio.encoder.a_line", encoder.a_line.line().clone()));
+        lines.push(("gpio.encoder.b_line", encoder.b_line.line().clone()));
+        if encoder.step_secs == 0 {
+            problems.push("gpio.encoder.step_secs: must be at least 1".to_owned());
+        }
+    }
+    let indicators = &config.gpio.indicators;
+    for (name, line) in [
+        ("gpio.indicators.game_line", &indicators.game_line),
+        ("gpio.indicators.left_line", &indicators.left_line),
+        ("gpio.indicators.right_line", &indicators.right_line),
+    ] {
+        if let Some(line) = line {
+            lines.push((name, line.clone()));
+        }
+    }
+    // The buttons and indicators so far are on the expander, if there is one
+    if let Some(ref expander) = config.gpio.expander {
+        let pins = match expander.kind {
+            ExpanderKind::Mcp23017 => 16,
+            ExpanderKind::Pcf8574 => 8,
+        };
+        for (name, line) in &lines {
+            match *line {
+                LineId::Offset(pin) if pin >= pins => {
+                    problems.push(format!("{name}: the expander has no pin {pin}"));
+                }
+                LineId::Offset(_) => {}
+                LineId::Name(_) => {
+                    problems.push(format!("{name}: expander pins can't be given by name"));
+                }
+            }
+        }
+        check_lines(&mut problems, &lines);
+        lines.clear();
+    }
+    if let Some(ref status_line) = config.gpio.status_line {
+        lines.push(("gpio.status_line", status_line.clone()));
+    }
+    if let Some(ref buzzer) = config.gpio.buzzer {
+        lines.push(("gpio.buzzer.line", buzzer.line.clone()));
+    }
+    if let Some(ref traffic_light) = config.gpio.traffic_light {
+        lines.push((
+            "gpio.traffic_light.red_line",
+            traffic_light.red_line.clone(),
+        ));
+        lines.push((
+            "gpio.traffic_light.amber_line",
+            traffic_light.amber_line.clone(),
+        ));
+        lines.push((
+            "gpio.traffic_light.green_line",
+            traffic_light.green_line.clone(),
+        ));
+    }
+    check_lines(&mut problems, &lines);
+    if let Some(ref led_strip) = config.gpio.led_strip {
+        if !(0.0..=1.0).contains(&led_strip.brightness) {
+            problems.push("gpio.led_strip.brightness: must be between 0 and 1".to_owned());
+        }
+    }
+    if let Some(ref serial) = config.gpio.serial {
+        if serial::baud_speed(serial.baud).is_none() {
+            problems.push(format!(
+                "gpio.serial.baud: {} is not supported",
+                serial.baud
+            ));
+        }
+    }
+    if let Some(ref seven_segment) = config.gpio.seven_segment {
+        if seven_segment.intensity > 15 {
+            problems.push("gpio.seven_segment.intensity: must be between 0 and 15".to_owned());
+        }
+    }
+
+    for preset in [&config.left_timer.preset, &config.right_timer.preset]
+        .into_iter()
+        .flatten()
+    {
+        if !config
+            .time_presets
+            .iter()
+            .any(|time_preset| &time_preset.name == preset)
+        {
+            problems.push(format!("preset: there is no time preset named {preset:?}"));
+        }
+    }
+    for (name, volume) in [
+        ("volume", config.volume),
+        ("duck_level", config.duck_level),
+        ("left_timer.volume", config.left_timer.volume),
+        ("right_timer.volume", config.right_timer.volume),
+    ] {
+        if !(0.0..=1.0).contains(&volume) {
+            problems.push(format!("{name}: must be between 0 and 1"));
+        }
+    }
+    if !matches!(config.rotation, 0 | 90 | 180 | 270) {
+        problems.push("rotation: must be 0, 90, 180 or 270".to_owned());
+    }
+    for (name, scale) in [
+        ("font.scale", config.font.scale),
+        ("font.digit_scale", config.font.digit_scale),
+    ] {
+        if scale <= 0.0 {
+            problems.push(format!("{name}: must be above 0"));
+        }
+    }
+    if config
+        .font
+        .reference_size
+        .is_some_and(|(width, height)| width == 0 || height == 0)
+    {
+        problems.push("font.reference_size: width and height must be above 0".to_owned());
+    }
+    if let Some(ref horn) = config.horn {
+        // Out of hearing, or past what the output can reproduce
+        if !(20.0..=20_000.0).contains(&horn.frequency_hz) {
+            problems.push("horn.frequency_hz: must be between 20 and 20000".to_owned());
+        }
+        if horn.blast_ms == 0 {
+            problems.push("horn.blast_ms: must be at least 1".to_owned());
+        }
+    }
+    if let Some(ref speech) = config.speech {
+        if speech.command.is_empty() {
+            problems.push("speech.command: must name a program".to_owned());
+        }
+    }
+    for (name, pan) in [
+        ("left_timer.pan", config.left_timer.pan),
+        ("right_timer.pan", config.right_timer.pan),
+    ] {
+        if !(-1.0..=1.0).contains(&pan) {
+            problems.push(format!("{name}: must be between -1 and 1"));
+        }
+    }
+    for (timer, flash) in [
+        ("game_timer", &config.game_timer.flash),
+        ("left_timer", &config.left_timer.flash),
+        ("right_timer", &config.right_timer.flash),
+    ] {
+        if flash.as_ref().is_some_and(|flash| flash.rate_hz <= 0.0) {
+            problems.push(format!("{timer}.flash.rate_hz: must be above 0"));
+        }
+    }
+    if config.refresh_interval_ms == 0 {
+        problems.push("refresh_interval_ms: must be at least 1".to_owned());
+    }
+    if let Some(ref random_delay) = config.random_delay {
+        if random_delay.min_ms > random_delay.max_ms {
+            problems.push("random_delay: min_ms is greater than max_ms".to_owned());
+        }
+    }
+    if let Some(ref scheduled_start) = config.scheduled_start {
+        if parse_wall_time(scheduled_start).is_none() {
+            problems.push(format!(
+                "scheduled_start: {scheduled_start:?} is not a HH:MM or HH:MM:SS time"
+            ));
+        }
+    }
+    for round in &config.rounds {
+        if round.ends == 0 || round.arrows_per_end == 0 {
+            problems.push(format!(
+                "rounds: {:?} needs at least one end and one arrow per end",
+                round.name
+            ));
+        }
+    }
+    for (action, keys) in &config.keybindings {
+        for name in keys.names() {
+            if parse_key(name).is_none() {
+                problems.push(format!(
+                    "keybindings: {name:?} (for {action:?}) is not a key name"
+                ));
+            }
+        }
+    }
+    return problems;
+}
+
+/// Reports lines used for more than one thing
+fn check_lines(problems: &mut Vec<String>, lines: &[(&str, LineId)]) {
+    for (i, (name, line)) in lines.iter().enumerate() {
+        if let Some((other, _)) = lines[..i].iter().find(|(_, other)| other == line) {
+            problems.push(format!("{name}: line {line} is already used by {other}"));
+        }
+    }
+}
+
+fn check_file(problems: &mut Vec<String>, name: &str, path: &Path) {
+    if !path.is_file() {
+        problems.push(format!("{name}: {path:?} does not exist"));
+    }
+}
@@ -0,0 +1,66 @@
+use std::{
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use crate::playlist::AUDIO_EXTENSIONS;
+
+/// Clips named for the remaining time they're played at, e.g. `2m.ogg`, `30s.ogg` or `5.ogg`
+/// (seconds), with `stop.ogg` played as a countdown runs out
+pub struct VoicePack {
+    // Shortest remaining time first
+    clips: Vec<(Duration, PathBuf)>,
+}
+impl VoicePack {
+    pub fn load(directory: &Path) -> Result<Self, String> {
+        let entries = std::fs::read_dir(directory).map_err(|err| err.to_string())?;
+        let mut clips = Vec::new();
+        for entry in entries {
+            let path = entry.map_err(|err| err.to_string())?.path();
+            let is_audio = path
+                .extension()
+                .and_then(|extension| extension.to_str())
+                .is_some_and(|extension| {
+                    AUDIO_EXTENSIONS.contains(&extension.to_ascii_lowercase().as_str())
+                });
+            let at = path
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .and_then(parse_clip_name);
+            match at {
+                Some(at) if is_audio => clips.push((at, path)),
+                _ => log::debug!("Skipping {path:?} in voice pack"),
+            }
+        }
+        if clips.is_empty() {
+            return Err("no clips found".to_owned());
+        }
+        clips.sort_by_key(|(at, _)| *at);
+        return Ok(Self { clips });
+    }
+    /// The clip for the shortest remaining time counted down past, between `from` (exclusive)
+    /// and `to`. Earlier ones are skipped rather than talked over.
+    pub fn clip_between(&self, from: Duration, to: Duration) -> Option<&Path> {
+        return self
+            .clips
+            .iter()
+            .find(|(at, _)| from > *at && to <= *at)
+            .map(|(_, path)| path.as_path());
+    }
+}
+
+/// The remaining time a clip is named for
+fn parse_clip_name(name: &str) -> Option<Duration> {
+    let name = name.to_ascii_lowercase();
+    if name == "stop" {
+        return Some(Duration::ZERO);
+    }
+    if let Some(minutes) = name.strip_suffix('m') {
+        return minutes
+            .parse()
+            .ok()
+            .map(|minutes: u64| Duration::from_secs(minutes * 60));
+    }
+    let secs = name.strip_suffix('s').unwrap_or(&name);
+    return secs.parse().ok().map(Duration::from_secs);
+}